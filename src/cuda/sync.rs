@@ -1,4 +1,5 @@
 use super::par::{GpuMap, GpuMapping};
+use crate::par::ReductionEpilogue;
 use crate::parir_compile_error;
 use crate::ir::ast::*;
 use crate::utils::err::*;
@@ -107,25 +108,20 @@ fn ensure_no_inter_block_sync_par_stmt(
     pars: &[GpuMap]
 ) -> CompileResult<()> {
     match stmt {
-        Stmt::For {var, body, par, i, ..} => {
+        Stmt::For {var, body, par, ..} => {
             let pars = if sync.contains(var) {
                 match &pars[0] {
                     GpuMap::Thread {..} => {
                         Ok(())
                     },
+                    // A reduction spanning more than one block no longer requires a global barrier:
+                    // it lowers to a per-block tree reduction followed by an atomic or two-pass
+                    // epilogue (see `select_reduction_epilogues`), so it is not rejected here.
+                    // A non-reduction inter-block sync point is resolved by splitting the loop body
+                    // into multiple kernel launches (see `split_inter_block_kernels`), so it is no
+                    // longer rejected here either.
                     GpuMap::Block {..} | GpuMap::ThreadBlock {..} => {
-                        let msg = if par.reduction {
-                            concat!(
-                                "Parallel reductions using more than 1024 ",
-                                "threads require inter-block synchronization, ",
-                                "which is not supported.")
-                        } else {
-                            concat!(
-                                "This parallel for-loop uses more than 1024 ",
-                                "threads and therefore requires inter-block ",
-                                "synchronization, which is not supported.")
-                        };
-                        parir_compile_error!(i, "{}", msg)
+                        Ok(())
                     },
                 }?;
                 &pars[1..]
@@ -201,10 +197,12 @@ fn ensure_no_inter_block_sync_stmts(
 /// for-loop is another parallelized for-loop, we do not need to synchronize it, as the iterations
 /// of the outer for-loop are assumed to be independent.
 ///
-/// Finally, the only general way to achieve synchronization across CUDA blocks is to split up code
-/// into separate kernels. The current implementation does not support this kind of transformation.
-/// Therefore, synchronization points are only allowed when it involves the threads of a single
-/// block, because in this case we can synchronize using a CUDA intrinsic.
+/// Finally, a synchronization point spanning more than one CUDA block has no single intrinsic that
+/// can satisfy it directly. It is resolved in one of three ways, decided elsewhere in this module:
+/// splitting the loop body into separate kernel launches (`split_inter_block_kernels`, the default,
+/// always-correct strategy), a tree-reduction-plus-atomic or two-pass epilogue for a reduction
+/// (`select_reduction_epilogues`), or, when the user has opted in, a single cooperative-grid kernel
+/// that calls `grid.sync()` in place of the barrier (`select_grid_sync_strategies`).
 pub fn identify_sync_points(
     ast: &Ast,
     gpu_mapping: &BTreeMap<Name, GpuMapping>
@@ -228,6 +226,339 @@ pub fn identify_sync_points(
     Ok(sync)
 }
 
+/// Select the multi-block epilogue for every parallel reduction for-loop whose mapping spans more
+/// than one CUDA block (a `GpuMap::Block`/`GpuMap::ThreadBlock` at the outermost level of its
+/// `GpuMapping`). A reduction that fits within a single block needs no epilogue at all — the
+/// per-block tree reduction already produces the final result — so only block-spanning reductions
+/// are returned here.
+pub fn select_reduction_epilogues(
+    ast: &Ast,
+    gpu_mapping: &BTreeMap<Name, GpuMapping>
+) -> BTreeMap<Name, ReductionEpilogue> {
+    let mut epilogues = BTreeMap::new();
+    select_reduction_epilogues_stmts(&ast.fun.body, gpu_mapping, &mut epilogues);
+    epilogues
+}
+
+fn select_reduction_epilogues_stmts(
+    stmts: &Vec<Stmt>,
+    gpu_mapping: &BTreeMap<Name, GpuMapping>,
+    acc: &mut BTreeMap<Name, ReductionEpilogue>
+) {
+    for stmt in stmts {
+        if let Stmt::For {var, body, par, ..} = stmt {
+            if let Some(op) = par.op.filter(|_| par.reduction) {
+                if let Some(m) = gpu_mapping.get(var) {
+                    if matches!(m.get_mapping()[0], GpuMap::Block {..} | GpuMap::ThreadBlock {..}) {
+                        acc.insert(var.clone(), op.epilogue());
+                    }
+                }
+            }
+            select_reduction_epilogues_stmts(body, gpu_mapping, acc);
+        }
+    }
+}
+
+/// How a for-loop's inter-block synchronization point is realized in the generated code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GridSyncStrategy {
+    /// The default, always-correct strategy: split the loop body into separate kernel launches at
+    /// the sync point (see `split_inter_block_kernels`).
+    Split,
+    /// Keep the loop as a single kernel launched through the cooperative-launch API and call
+    /// `grid.sync()` in place of the barrier. Avoids the register/shared-memory spill to global
+    /// memory that splitting would otherwise force, but is only valid when the launch is
+    /// guaranteed to fit as one cooperative grid (see `select_grid_sync_strategies`).
+    Cooperative,
+}
+
+/// Select a `GridSyncStrategy` for every for-loop that has opted into cooperative-grid
+/// synchronization (`GpuMapping::cooperative`) and whose mapping spans more than one CUDA block.
+/// Loops that did not opt in are left out of the map entirely, so callers should fall back to
+/// `Split` for any block-spanning sync point missing from it.
+///
+/// A cooperative launch can only run as many blocks as the device can keep resident
+/// simultaneously, since `grid.sync()` deadlocks if any block in the grid has not yet been
+/// scheduled. `max_cooperative_blocks` is the device's occupancy limit for this kernel (the
+/// product of `cudaOccupancyMaxActiveBlocksPerMultiprocessor` and the SM count, computed by the
+/// caller); a loop whose grid exceeds it is rejected with a compile error referencing the loop
+/// instead of being silently downgraded to `Split`; the user opted in to a specific lowering, so
+/// falling back would leave the cost model they asked for silently unmet.
+pub fn select_grid_sync_strategies(
+    ast: &Ast,
+    gpu_mapping: &BTreeMap<Name, GpuMapping>,
+    max_cooperative_blocks: i64
+) -> CompileResult<BTreeMap<Name, GridSyncStrategy>> {
+    let mut strategies = BTreeMap::new();
+    select_grid_sync_strategies_stmts(&ast.fun.body, gpu_mapping, max_cooperative_blocks, &mut strategies)?;
+    Ok(strategies)
+}
+
+fn select_grid_sync_strategies_stmts(
+    stmts: &Vec<Stmt>,
+    gpu_mapping: &BTreeMap<Name, GpuMapping>,
+    max_cooperative_blocks: i64,
+    acc: &mut BTreeMap<Name, GridSyncStrategy>
+) -> CompileResult<()> {
+    for stmt in stmts {
+        if let Stmt::For {var, body, i, ..} = stmt {
+            if let Some(m) = gpu_mapping.get(var) {
+                let spans_blocks =
+                    matches!(m.get_mapping()[0], GpuMap::Block {..} | GpuMap::ThreadBlock {..});
+                if m.cooperative && spans_blocks {
+                    let blocks = grid_block_count(m);
+                    if blocks > max_cooperative_blocks {
+                        let msg = format!(
+                            "This loop launches {blocks} blocks, which exceeds the device's \
+                             cooperative-launch occupancy limit of {max_cooperative_blocks}; \
+                             remove the cooperative launch hint to fall back to kernel splitting.");
+                        parir_compile_error!(i, "{}", msg)?;
+                    }
+                    acc.insert(var.clone(), GridSyncStrategy::Cooperative);
+                }
+            }
+            select_grid_sync_strategies_stmts(body, gpu_mapping, max_cooperative_blocks, acc)?;
+        }
+    }
+    Ok(())
+}
+
+/// The total number of CUDA blocks a `GpuMapping`'s grid launches, as the product of the block
+/// counts of its `Block`/`ThreadBlock` mapping entries (one per grid dimension).
+fn grid_block_count(m: &GpuMapping) -> i64 {
+    m.get_mapping()
+        .iter()
+        .filter_map(|g| match g {
+            GpuMap::Block {n, ..} | GpuMap::ThreadBlock {n, ..} => Some(*n),
+            GpuMap::Thread {..} => None,
+        })
+        .product()
+}
+
+/// A straight-line segment of a parallel for-loop body that becomes a single CUDA kernel launch.
+/// Segments are separated by inter-block synchronization points: CUDA guarantees that kernel N
+/// completes before kernel N+1 starts, which is the only general way to synchronize across blocks.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KernelSegment {
+    pub stmts: Vec<Stmt>,
+    /// Variables produced by an earlier segment and read in this one. Because registers
+    /// and shared memory do not survive a kernel boundary, these values must be materialized in
+    /// global memory between launches.
+    pub live_across: BTreeSet<Name>,
+}
+
+/// Split a parallel for-loop body into the maximal straight-line segments separated by inter-block
+/// synchronization points (a sync point sits after each parallelized inner for-loop whose iteration
+/// variable is in `sync`). Each boundary-crossing value — written in an earlier segment and read in
+/// a later one — is recorded on every later segment that reads it so lowering knows to route it
+/// through global memory. A body with no inter-block sync point yields a single segment.
+fn split_body_at_sync_points(body: &Vec<Stmt>, sync: &BTreeSet<Name>) -> Vec<KernelSegment> {
+    let mut segments: Vec<Vec<Stmt>> = vec![];
+    let mut current: Vec<Stmt> = vec![];
+    for stmt in body {
+        current.push(stmt.clone());
+        if let Stmt::For {var, par, ..} = stmt {
+            if par.is_parallel() && sync.contains(var) {
+                segments.push(std::mem::take(&mut current));
+            }
+        }
+    }
+    if !current.is_empty() || segments.is_empty() {
+        segments.push(current);
+    }
+
+    // A value crosses a boundary if it is written in an earlier segment and read in this one.
+    let mut written_before = BTreeSet::new();
+    let mut result = Vec::with_capacity(segments.len());
+    for seg in segments {
+        let reads = read_variables(&seg);
+        let live_across = reads.intersection(&written_before).cloned().collect();
+        written_before.extend(write_variables(&seg));
+        result.push(KernelSegment {stmts: seg, live_across});
+    }
+    result
+}
+
+/// The variables assigned anywhere in a sequence of statements (through a `Definition` binding, an
+/// `Assign` target, or a `For` iteration variable).
+fn write_variables(stmts: &Vec<Stmt>) -> BTreeSet<Name> {
+    stmts.iter().fold(BTreeSet::new(), |mut acc, stmt| {
+        match stmt {
+            Stmt::Definition {id, ..} => { acc.insert(id.clone()); },
+            Stmt::Assign {dst, ..} => { acc.extend(dst.free_variables()); },
+            Stmt::For {var, body, ..} => {
+                acc.insert(var.clone());
+                acc.extend(write_variables(body));
+            },
+            Stmt::While {body, ..} => acc.extend(write_variables(body)),
+            Stmt::If {thn, els, ..} => {
+                acc.extend(write_variables(thn));
+                acc.extend(write_variables(els));
+            }
+        }
+        acc
+    })
+}
+
+/// The variables read anywhere in a sequence of statements.
+fn read_variables(stmts: &Vec<Stmt>) -> BTreeSet<Name> {
+    stmts.iter().fold(BTreeSet::new(), |mut acc, stmt| {
+        acc.extend(stmt.free_variables());
+        acc
+    })
+}
+
+/// Split the body of every for-loop that has an entry in `gpu_mapping` at its synchronization
+/// points into an ordered list of kernel segments, keyed on the loop's iteration variable. Every
+/// remaining sync point splits the body: whether it spans more than one block, and whether it
+/// belongs to a reduction, is not checked. The result holds only the segments; it carries no
+/// launch configuration. A loop whose body forms a single segment is not returned.
+pub fn split_inter_block_kernels(
+    ast: &Ast,
+    gpu_mapping: &BTreeMap<Name, GpuMapping>
+) -> CompileResult<BTreeMap<Name, Vec<KernelSegment>>> {
+    let sync = identify_sync_points_for_split(ast, gpu_mapping)?;
+    let mut kernels = BTreeMap::new();
+    collect_split_kernels_stmts(&ast.fun.body, &sync, gpu_mapping, &mut kernels);
+    Ok(kernels)
+}
+
+/// Like `identify_sync_points`, but collecting the sync points without asserting that they stay
+/// within a block — the whole point of the splitting pass is to accept the block-spanning ones.
+fn identify_sync_points_for_split(
+    ast: &Ast,
+    _gpu_mapping: &BTreeMap<Name, GpuMapping>
+) -> CompileResult<BTreeSet<Name>> {
+    let sync = collect_sync_points_stmts(Ok(BTreeSet::new()), &ast.fun.body)?;
+    Ok(remove_redundant_sync_stmts(sync, &ast.fun.body))
+}
+
+fn collect_split_kernels_stmts(
+    stmts: &Vec<Stmt>,
+    sync: &BTreeSet<Name>,
+    gpu_mapping: &BTreeMap<Name, GpuMapping>,
+    acc: &mut BTreeMap<Name, Vec<KernelSegment>>
+) {
+    for stmt in stmts {
+        if let Stmt::For {var, body, ..} = stmt {
+            if gpu_mapping.contains_key(var) {
+                let segments = split_body_at_sync_points(body, sync);
+                if segments.len() > 1 {
+                    acc.insert(var.clone(), segments);
+                }
+            }
+            collect_split_kernels_stmts(body, sync, gpu_mapping, acc);
+        }
+    }
+}
+
+/// The buffers a single statement reads and writes, restricted to the shared-memory buffers in
+/// scope. A statement's assignment target is a write; every other shared buffer it mentions is a
+/// read. We conservatively treat every shared read as a potential neighbor access (a read indexed
+/// by a thread other than the one that wrote the element): this IR does not retain enough index
+/// structure after lowering to prove that an access stays on the writing thread's own index, so we
+/// assume it may not and insert the barrier. A same-index read-then-write would be satisfied by the
+/// thread's own program order and could skip the barrier, but recognizing that case safely requires
+/// the index expressions, which are not available here.
+fn shared_access(stmt: &Stmt, shared: &BTreeSet<Name>) -> (BTreeSet<Name>, BTreeSet<Name>) {
+    match stmt {
+        Stmt::Assign {dst, ..} => {
+            let writes: BTreeSet<Name> =
+                dst.free_variables().intersection(shared).cloned().collect();
+            // A statement can both read and write the same shared buffer, e.g. a shift/stencil
+            // `smem[tid] = smem[tid + 1]`. Keep the buffer in `reads` even though it is also in
+            // `writes`: dropping it would silently skip the wrap-around barrier this exact reuse
+            // pattern needs, and a missed `__syncthreads()` is wrong GPU output, not just a
+            // missed optimization.
+            let reads = stmt.free_variables().intersection(shared).cloned().collect();
+            (reads, writes)
+        },
+        _ => {
+            let reads = stmt.free_variables().intersection(shared).cloned().collect();
+            (reads, BTreeSet::new())
+        }
+    }
+}
+
+/// Analyze each parallel for-loop body for write-after-read hazards on reused shared-memory buffers
+/// and return, per loop iteration variable, the set of statement positions after which a
+/// `__syncthreads()` must be inserted. A barrier is required whenever a write to a shared buffer is
+/// ordered after a neighbor read of that buffer within the same loop body, including the wrap-around
+/// from the loop tail back to its head (`smem[tid] = x; ...; y = smem[tid+1]`), so the next
+/// iteration's producers wait for this iteration's consumers.
+pub fn insert_war_hazard_syncs(
+    ast: &Ast,
+    gpu_mapping: &BTreeMap<Name, GpuMapping>
+) -> BTreeMap<Name, BTreeSet<usize>> {
+    let mut barriers = BTreeMap::new();
+    war_hazard_syncs_stmts(&ast.fun.body, gpu_mapping, &mut barriers);
+    barriers
+}
+
+fn war_hazard_syncs_stmts(
+    stmts: &Vec<Stmt>,
+    gpu_mapping: &BTreeMap<Name, GpuMapping>,
+    acc: &mut BTreeMap<Name, BTreeSet<usize>>
+) {
+    for stmt in stmts {
+        if let Stmt::For {var, body, par, ..} = stmt {
+            if par.is_parallel() && gpu_mapping.contains_key(var) {
+                let points = body_war_hazard_points(body);
+                if !points.is_empty() {
+                    acc.insert(var.clone(), points);
+                }
+            }
+            war_hazard_syncs_stmts(body, gpu_mapping, acc);
+        }
+    }
+}
+
+/// Find the statement positions within one loop body after which a barrier is needed. Walking the
+/// body once, we remember for each shared buffer the last position at which it was read; a
+/// subsequent write to that buffer demands a barrier between the read and the write. A read with no
+/// following write in the body is carried around the loop tail, so a write earlier in the body (a
+/// reuse across iterations) still gets a tail barrier.
+fn body_war_hazard_points(body: &Vec<Stmt>) -> BTreeSet<usize> {
+    let shared = shared_buffers(body);
+    let mut points = BTreeSet::new();
+    let mut last_read: BTreeMap<Name, usize> = BTreeMap::new();
+    for (pos, stmt) in body.iter().enumerate() {
+        let (reads, writes) = shared_access(stmt, &shared);
+        for buf in &writes {
+            if let Some(read_pos) = last_read.remove(buf) {
+                // A write after a read of the same buffer: insert a barrier right after the read.
+                points.insert(read_pos);
+            }
+        }
+        for buf in reads {
+            last_read.insert(buf, pos);
+        }
+    }
+    // Any buffer still read-but-not-yet-written wraps around: if it is ever written in the body the
+    // reuse happens across iterations, so emit the tail barrier.
+    let tail = body.len().saturating_sub(1);
+    for (buf, read_pos) in last_read {
+        let written = body.iter().any(|s| shared_access(s, &shared).1.contains(&buf));
+        if written {
+            points.insert(read_pos.max(tail));
+        }
+    }
+    points
+}
+
+/// The shared-memory buffers allocated within a loop body. A buffer is a value bound by a
+/// `Definition` local to the body; shared-memory promotion turns exactly these loop-local
+/// allocations into `__shared__` arrays, so they are the buffers whose reuse can race across
+/// threads.
+fn shared_buffers(body: &Vec<Stmt>) -> BTreeSet<Name> {
+    body.iter().fold(BTreeSet::new(), |mut acc, stmt| {
+        if let Stmt::Definition {id, ..} = stmt {
+            acc.insert(id.clone());
+        }
+        acc
+    })
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -277,7 +608,8 @@ mod test {
                 GpuMap::Block {n: 24, dim: Dim::X, mult: 1},
                 GpuMap::Thread {n: 64, dim: Dim::X, mult: 1}
             ],
-            tpb: DEFAULT_TPB
+            tpb: DEFAULT_TPB,
+            cooperative: false
         };
         let mapping = make_mapping(vec![(x.clone(), m)]);
         let expected = BTreeSet::from([y]);
@@ -285,8 +617,9 @@ mod test {
     }
 
     #[test]
-    #[should_panic]
-    fn inter_block_sync_point_err() {
+    fn inter_block_sync_point_splits_into_kernels() {
+        // Two inter-block parallel phases under an outer block loop are no longer rejected; instead
+        // the body is split into two kernel segments.
         let x = id("x");
         let y = id("y");
         let z = id("z");
@@ -297,5 +630,47 @@ mod test {
         let m = GpuMapping::default().add_parallelism(2048).add_parallelism(24).rev_mapping();
         let mapping = make_mapping(vec![(x.clone(), m)]);
         identify_sync_points(&ast, &mapping).unwrap();
+        let kernels = split_inter_block_kernels(&ast, &mapping).unwrap();
+        assert_eq!(kernels.get(&x).map(|k| k.len()), Some(2));
+    }
+
+    fn block_spanning_mapping(nblocks: i64, cooperative: bool) -> GpuMapping {
+        GpuMapping {
+            grid: LaunchArgs::default()
+                .with_blocks_dim(&Dim::X, nblocks)
+                .with_threads_dim(&Dim::X, 64),
+            mapping: vec![
+                GpuMap::Block {n: nblocks, dim: Dim::X, mult: 1},
+                GpuMap::Thread {n: 64, dim: Dim::X, mult: 1}
+            ],
+            tpb: DEFAULT_TPB,
+            cooperative
+        }
+    }
+
+    #[test]
+    fn cooperative_loop_within_occupancy_limit_uses_grid_sync() {
+        let x = id("x");
+        let ast = make_ast(vec![for_loop(x.clone(), 24, vec![])]);
+        let mapping = make_mapping(vec![(x.clone(), block_spanning_mapping(24, true))]);
+        let strategies = select_grid_sync_strategies(&ast, &mapping, 32).unwrap();
+        assert_eq!(strategies.get(&x), Some(&GridSyncStrategy::Cooperative));
+    }
+
+    #[test]
+    fn cooperative_loop_exceeding_occupancy_limit_is_rejected() {
+        let x = id("x");
+        let ast = make_ast(vec![for_loop(x.clone(), 24, vec![])]);
+        let mapping = make_mapping(vec![(x.clone(), block_spanning_mapping(24, true))]);
+        assert!(select_grid_sync_strategies(&ast, &mapping, 16).is_err());
+    }
+
+    #[test]
+    fn non_cooperative_loop_is_left_out_of_strategy_map() {
+        let x = id("x");
+        let ast = make_ast(vec![for_loop(x.clone(), 24, vec![])]);
+        let mapping = make_mapping(vec![(x.clone(), block_spanning_mapping(24, false))]);
+        let strategies = select_grid_sync_strategies(&ast, &mapping, 32).unwrap();
+        assert_eq!(strategies.get(&x), None);
     }
 }
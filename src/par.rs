@@ -1,12 +1,147 @@
+use crate::py::ast::{Builtin, ElemSize, Expr, Type, UnOp};
+use crate::utils::info::*;
+
 use pyo3::prelude::*;
 
 pub const REDUCE_PAR_LABEL: &'static str = "_reduce";
 
+/// The associative (and, except for the comparison-based variants, commutative) operator performed
+/// by a parallel reduction. Carrying the operator explicitly on the `GpuReduction` lets lowering
+/// emit a tree/warp-shuffle reduction keyed on the operator, rather than recovering it by
+/// pattern-matching the shape of the accumulator statement.
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReduceOp {
+    Sum, Prod, Max, Min, Any, All, ArgMax, ArgMin,
+}
+
+impl ReduceOp {
+    /// The identity element of the reduction for a given element size, returned as the literal that
+    /// initializes the accumulator. `Max`/`Min` use negative/positive infinity for floating-point
+    /// element sizes, the extreme value representable in `sz` for an unsigned integer size (`0` for
+    /// `Max`, since an unsigned type has no negative extreme to fall below), and the extreme signed
+    /// `i64` value otherwise. `ArgMax`/`ArgMin` share the identity of their value component (the
+    /// paired index is irrelevant until the first comparison).
+    pub fn identity(&self, sz: &ElemSize) -> ReduceIdentity {
+        match self {
+            ReduceOp::Sum => ReduceIdentity::Int(0),
+            ReduceOp::Prod => ReduceIdentity::Int(1),
+            ReduceOp::Any => ReduceIdentity::Bool(false),
+            ReduceOp::All => ReduceIdentity::Bool(true),
+            ReduceOp::Max | ReduceOp::ArgMax => {
+                if sz.is_floating_point() {
+                    ReduceIdentity::NegInf
+                } else if sz.is_unsigned_integer() {
+                    ReduceIdentity::UInt(0)
+                } else {
+                    ReduceIdentity::Int(i64::MIN)
+                }
+            },
+            ReduceOp::Min | ReduceOp::ArgMin => {
+                if sz.is_floating_point() {
+                    ReduceIdentity::Inf
+                } else if sz.is_unsigned_integer() {
+                    ReduceIdentity::UInt(unsigned_max(sz))
+                } else {
+                    ReduceIdentity::Int(i64::MAX)
+                }
+            },
+        }
+    }
+
+    /// Materialize `self.identity(sz)` as a literal `Expr` of type `sz`, suitable for
+    /// `GpuReduction::init`; the infinities are the exception, built from `Builtin::Inf` with type
+    /// `float64` whatever `sz` is. `UInt` is carried through `Expr::Int`'s `i64` payload by its two's
+    /// complement bit pattern, the same representation codegen already relies on to reinterpret an
+    /// `i64` literal as the target unsigned C type.
+    pub fn identity_expr(&self, sz: &ElemSize, i: Info) -> Expr {
+        let ty = Type::Tensor {sz: sz.clone(), shape: vec![], strides: None};
+        match self.identity(sz) {
+            ReduceIdentity::Int(v) => Expr::Int {v, ty, i},
+            ReduceIdentity::UInt(v) => Expr::Int {v: v as i64, ty, i},
+            ReduceIdentity::Bool(v) => Expr::Bool {v, ty, i},
+            ReduceIdentity::Inf => {
+                let inf_ty = Type::Tensor {sz: ElemSize::F64, shape: vec![], strides: None};
+                Expr::Builtin {func: Builtin::Inf, args: vec![], ty: inf_ty, i}
+            },
+            ReduceIdentity::NegInf => {
+                let inf_ty = Type::Tensor {sz: ElemSize::F64, shape: vec![], strides: None};
+                let inf = Expr::Builtin {func: Builtin::Inf, args: vec![], ty: inf_ty.clone(), i: i.clone()};
+                Expr::UnOp {op: UnOp::Sub, arg: Box::new(inf), ty: inf_ty, i}
+            },
+        }
+    }
+
+    /// Whether the reduction tracks a paired (value, index) tuple, in which case the accumulator
+    /// has a `Type::Tuple` shape threaded through the reduction.
+    pub fn is_arg_reduction(&self) -> bool {
+        matches!(self, ReduceOp::ArgMax | ReduceOp::ArgMin)
+    }
+
+    /// Whether CUDA offers a native atomic matching this operator (`atomicAdd`, `atomicMax`,
+    /// `atomicMin`, and `atomicOr`/`atomicAnd` standing in for `Any`/`All`). `Prod` has no atomic
+    /// multiply, and the `Arg*` variants accumulate a (value, index) pair rather than a single
+    /// scalar, so neither can use the single-instruction epilogue.
+    pub fn supports_atomic(&self) -> bool {
+        matches!(self, ReduceOp::Sum | ReduceOp::Max | ReduceOp::Min | ReduceOp::Any | ReduceOp::All)
+    }
+
+    /// The epilogue a multi-block reduction over this operator should lower to: the cheaper
+    /// `Atomic` strategy when CUDA has a matching atomic, otherwise the always-applicable
+    /// `TwoPass` strategy.
+    pub fn epilogue(&self) -> ReductionEpilogue {
+        if self.supports_atomic() {
+            ReductionEpilogue::Atomic
+        } else {
+            ReductionEpilogue::TwoPass
+        }
+    }
+}
+
+/// How a parallel reduction spanning more than one CUDA block combines its per-block partial
+/// results into the final accumulator. Chosen once lowering knows the reduction crosses a block
+/// boundary and cannot rely on a single `__syncthreads()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReductionEpilogue {
+    /// Each block reduces its slice into shared memory with the standard tree reduction, then one
+    /// thread per block folds the block-local partial directly into the global result with a CUDA
+    /// atomic. Only valid for operators with a native atomic (see `ReduceOp::supports_atomic`).
+    Atomic,
+    /// Each block reduces its slice and writes the partial to a scratch global array, which a
+    /// second kernel launch reduces to the final result. Reuses the kernel-splitting machinery
+    /// from `cuda::sync`, so it is always available regardless of the operator.
+    TwoPass,
+}
+
+/// The identity element of a reduction, prior to being materialized as a literal `Expr` of the
+/// reduction's element type. `UInt` is kept separate from `Int` because the unsigned extreme
+/// values (e.g. `u64::MAX`) are not representable in an `i64`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReduceIdentity {
+    Int(i64),
+    UInt(u64),
+    Bool(bool),
+    Inf,
+    NegInf,
+}
+
+/// The largest value representable by an unsigned `ElemSize`, or `0` for anything else (callers
+/// only invoke this once `sz.is_unsigned_integer()` has already been checked).
+fn unsigned_max(sz: &ElemSize) -> u64 {
+    match sz {
+        ElemSize::U8 => u8::MAX as u64,
+        ElemSize::U16 => u16::MAX as u64,
+        ElemSize::U32 => u32::MAX as u64,
+        ElemSize::U64 => u64::MAX,
+        _ => 0,
+    }
+}
+
 #[pyclass]
 #[derive(Clone, Debug)]
 pub enum ParKind {
     GpuThreads(i64),
-    GpuReduction {},
+    GpuReduction {op: ReduceOp, init: Expr},
 }
 
 #[pymethods]
@@ -0,0 +1,308 @@
+use super::ast::*;
+
+use std::collections::BTreeMap;
+use std::ops::Index;
+
+/// A lightweight, copyable handle into an [`Arena`]. Cloning an `ExprId` is a `usize` copy
+/// regardless of how large the subtree it points to is, which is what makes the arena useful for
+/// passes that want to compare or duplicate subtrees without walking them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ExprId(usize);
+
+/// An [`Expr`] node with its immediate children replaced by [`ExprId`] handles into the same
+/// [`Arena`], rather than nested `Box<Expr>` subtrees. This is the arena's own node
+/// representation; `Arena::intern`/`Arena::to_expr` convert between it and the ordinary
+/// `Box<Expr>`-based `Expr` that the rest of the front-end builds and pattern-matches on.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ArenaExpr {
+    Var {id: Name, ty: Type, i: Info},
+    String {v: String, ty: Type, i: Info},
+    Bool {v: bool, ty: Type, i: Info},
+    Int {v: i64, ty: Type, i: Info},
+    Float {v: f64, ty: Type, i: Info},
+    UnOp {op: UnOp, arg: ExprId, ty: Type, i: Info},
+    BinOp {lhs: ExprId, op: BinOp, rhs: ExprId, ty: Type, i: Info},
+    IfExpr {cond: ExprId, thn: ExprId, els: ExprId, ty: Type, i: Info},
+    Subscript {target: ExprId, idx: ExprId, ty: Type, i: Info},
+    Tuple {elems: Vec<ExprId>, ty: Type, i: Info},
+    Dict {fields: BTreeMap<String, ExprId>, ty: Type, i: Info},
+    Builtin {func: Builtin, args: Vec<ExprId>, ty: Type, i: Info},
+    Convert {e: ExprId, ty: Type},
+    Slice {lo: Option<ExprId>, hi: Option<ExprId>, step: Option<ExprId>, ty: Type, i: Info},
+    Broadcast {e: ExprId, shape: Vec<i64>, ty: Type},
+}
+
+/// An append-only, hash-consing store of [`ArenaExpr`] nodes. Interning the same shape twice
+/// (same variant, same children `ExprId`s, same scalar payload and `Type`) returns the same
+/// `ExprId`, so identical subtrees genuinely share one node instead of each being a separate
+/// in-memory copy — this is what gives `ExprId` equality an O(1) structural-equality check, and
+/// `ExprId` cloning an O(1) subtree "clone".
+///
+/// The dedup key deliberately ignores `Info`: two occurrences of the same value built at
+/// different source positions still denote the same value, so they still share a node. The first
+/// occurrence's `Info` is the one kept; `to_expr` reports that span for every shared use.
+#[derive(Clone, Debug, Default)]
+pub struct Arena {
+    nodes: Vec<ArenaExpr>,
+    by_shape: BTreeMap<String, ExprId>,
+}
+
+impl Arena {
+    pub fn new() -> Self {
+        Arena {nodes: vec![], by_shape: BTreeMap::new()}
+    }
+
+    /// Store a node built from already-interned children and return its handle, reusing an
+    /// existing node of the same shape if one is already in the arena.
+    fn alloc(&mut self, node: ArenaExpr) -> ExprId {
+        let key = shape_key(&node);
+        if let Some(id) = self.by_shape.get(&key) {
+            *id
+        } else {
+            let id = ExprId(self.nodes.len());
+            self.nodes.push(node);
+            self.by_shape.insert(key, id);
+            id
+        }
+    }
+
+    pub fn get(&self, id: ExprId) -> &ArenaExpr {
+        &self.nodes[id.0]
+    }
+
+    /// Intern an expression tree, allocating one (deduplicated) arena node per distinct
+    /// sub-expression. Children are interned first so the parent's `ArenaExpr` stores their real
+    /// `ExprId`s rather than a re-embedded copy of their subtree.
+    pub fn intern(&mut self, e: &Expr) -> ExprId {
+        let node = match e {
+            Expr::Var {id, ty, i} => ArenaExpr::Var {id: id.clone(), ty: ty.clone(), i: i.clone()},
+            Expr::String {v, ty, i} =>
+                ArenaExpr::String {v: v.clone(), ty: ty.clone(), i: i.clone()},
+            Expr::Bool {v, ty, i} => ArenaExpr::Bool {v: *v, ty: ty.clone(), i: i.clone()},
+            Expr::Int {v, ty, i} => ArenaExpr::Int {v: *v, ty: ty.clone(), i: i.clone()},
+            Expr::Float {v, ty, i} => ArenaExpr::Float {v: *v, ty: ty.clone(), i: i.clone()},
+            Expr::UnOp {op, arg, ty, i} => {
+                let arg = self.intern(arg);
+                ArenaExpr::UnOp {op: op.clone(), arg, ty: ty.clone(), i: i.clone()}
+            },
+            Expr::BinOp {lhs, op, rhs, ty, i} => {
+                let lhs = self.intern(lhs);
+                let rhs = self.intern(rhs);
+                ArenaExpr::BinOp {lhs, op: op.clone(), rhs, ty: ty.clone(), i: i.clone()}
+            },
+            Expr::IfExpr {cond, thn, els, ty, i} => {
+                let cond = self.intern(cond);
+                let thn = self.intern(thn);
+                let els = self.intern(els);
+                ArenaExpr::IfExpr {cond, thn, els, ty: ty.clone(), i: i.clone()}
+            },
+            Expr::Subscript {target, idx, ty, i} => {
+                let target = self.intern(target);
+                let idx = self.intern(idx);
+                ArenaExpr::Subscript {target, idx, ty: ty.clone(), i: i.clone()}
+            },
+            Expr::Tuple {elems, ty, i} => {
+                let elems = elems.iter().map(|e| self.intern(e)).collect();
+                ArenaExpr::Tuple {elems, ty: ty.clone(), i: i.clone()}
+            },
+            Expr::Dict {fields, ty, i} => {
+                let fields = fields.iter().map(|(k, v)| (k.clone(), self.intern(v))).collect();
+                ArenaExpr::Dict {fields, ty: ty.clone(), i: i.clone()}
+            },
+            Expr::Builtin {func, args, ty, i} => {
+                let args = args.iter().map(|e| self.intern(e)).collect();
+                ArenaExpr::Builtin {func: func.clone(), args, ty: ty.clone(), i: i.clone()}
+            },
+            Expr::Convert {e, ty} => {
+                let e = self.intern(e);
+                ArenaExpr::Convert {e, ty: ty.clone()}
+            },
+            Expr::Slice {lo, hi, step, ty, i} => {
+                let lo = lo.as_ref().map(|e| self.intern(e));
+                let hi = hi.as_ref().map(|e| self.intern(e));
+                let step = step.as_ref().map(|e| self.intern(e));
+                ArenaExpr::Slice {lo, hi, step, ty: ty.clone(), i: i.clone()}
+            },
+            Expr::Broadcast {e, shape, ty} => {
+                let e = self.intern(e);
+                ArenaExpr::Broadcast {e, shape: shape.clone(), ty: ty.clone()}
+            },
+        };
+        self.alloc(node)
+    }
+
+    /// Rebuild the ordinary `Box<Expr>`-based tree rooted at `id`. Used by passes that interned a
+    /// tree to compare or deduplicate it and now need to hand an `Expr` back to code that is not
+    /// arena-aware.
+    pub fn to_expr(&self, id: ExprId) -> Expr {
+        match self.get(id).clone() {
+            ArenaExpr::Var {id, ty, i} => Expr::Var {id, ty, i},
+            ArenaExpr::String {v, ty, i} => Expr::String {v, ty, i},
+            ArenaExpr::Bool {v, ty, i} => Expr::Bool {v, ty, i},
+            ArenaExpr::Int {v, ty, i} => Expr::Int {v, ty, i},
+            ArenaExpr::Float {v, ty, i} => Expr::Float {v, ty, i},
+            ArenaExpr::UnOp {op, arg, ty, i} =>
+                Expr::UnOp {op, arg: Box::new(self.to_expr(arg)), ty, i},
+            ArenaExpr::BinOp {lhs, op, rhs, ty, i} =>
+                Expr::BinOp {
+                    lhs: Box::new(self.to_expr(lhs)), op, rhs: Box::new(self.to_expr(rhs)), ty, i
+                },
+            ArenaExpr::IfExpr {cond, thn, els, ty, i} =>
+                Expr::IfExpr {
+                    cond: Box::new(self.to_expr(cond)),
+                    thn: Box::new(self.to_expr(thn)),
+                    els: Box::new(self.to_expr(els)),
+                    ty, i
+                },
+            ArenaExpr::Subscript {target, idx, ty, i} =>
+                Expr::Subscript {
+                    target: Box::new(self.to_expr(target)), idx: Box::new(self.to_expr(idx)), ty, i
+                },
+            ArenaExpr::Tuple {elems, ty, i} => {
+                let elems = elems.into_iter().map(|id| self.to_expr(id)).collect();
+                Expr::Tuple {elems, ty, i}
+            },
+            ArenaExpr::Dict {fields, ty, i} => {
+                let fields = fields.into_iter().map(|(k, id)| (k, self.to_expr(id))).collect();
+                Expr::Dict {fields, ty, i}
+            },
+            ArenaExpr::Builtin {func, args, ty, i} => {
+                let args = args.into_iter().map(|id| self.to_expr(id)).collect();
+                Expr::Builtin {func, args, ty, i}
+            },
+            ArenaExpr::Convert {e, ty} => Expr::Convert {e: Box::new(self.to_expr(e)), ty},
+            ArenaExpr::Slice {lo, hi, step, ty, i} => {
+                let apply = |id: Option<ExprId>| id.map(|id| Box::new(self.to_expr(id)));
+                Expr::Slice {lo: apply(lo), hi: apply(hi), step: apply(step), ty, i}
+            },
+            ArenaExpr::Broadcast {e, shape, ty} =>
+                Expr::Broadcast {e: Box::new(self.to_expr(e)), shape, ty},
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+impl Index<ExprId> for Arena {
+    type Output = ArenaExpr;
+
+    fn index(&self, id: ExprId) -> &ArenaExpr {
+        self.get(id)
+    }
+}
+
+/// A string key capturing everything about a node that determines its identity for hash-consing
+/// (variant, children `ExprId`s, scalar payload, and `Type`) while leaving out `Info`. Built with
+/// `Debug` formatting rather than a derived `Eq`/`Hash`/`Ord` on `ArenaExpr` because `Float`'s
+/// `f64` payload has none of those; it is keyed on its bit pattern (`to_bits`), and the string
+/// key keeps this independent of whether `Type`/`Info` ever grow such derives themselves.
+fn shape_key(node: &ArenaExpr) -> String {
+    match node {
+        ArenaExpr::Float {v, ty, ..} => format!("Float|{}|{ty:?}", v.to_bits()),
+        ArenaExpr::Var {id, ty, ..} => format!("Var|{id:?}|{ty:?}"),
+        ArenaExpr::String {v, ty, ..} => format!("String|{v:?}|{ty:?}"),
+        ArenaExpr::Bool {v, ty, ..} => format!("Bool|{v}|{ty:?}"),
+        ArenaExpr::Int {v, ty, ..} => format!("Int|{v}|{ty:?}"),
+        ArenaExpr::UnOp {op, arg, ty, ..} => format!("UnOp|{op:?}|{arg:?}|{ty:?}"),
+        ArenaExpr::BinOp {lhs, op, rhs, ty, ..} => format!("BinOp|{lhs:?}|{op:?}|{rhs:?}|{ty:?}"),
+        ArenaExpr::IfExpr {cond, thn, els, ty, ..} =>
+            format!("IfExpr|{cond:?}|{thn:?}|{els:?}|{ty:?}"),
+        ArenaExpr::Subscript {target, idx, ty, ..} =>
+            format!("Subscript|{target:?}|{idx:?}|{ty:?}"),
+        ArenaExpr::Tuple {elems, ty, ..} => format!("Tuple|{elems:?}|{ty:?}"),
+        ArenaExpr::Dict {fields, ty, ..} => format!("Dict|{fields:?}|{ty:?}"),
+        ArenaExpr::Builtin {func, args, ty, ..} => format!("Builtin|{func:?}|{args:?}|{ty:?}"),
+        ArenaExpr::Convert {e, ty} => format!("Convert|{e:?}|{ty:?}"),
+        ArenaExpr::Slice {lo, hi, step, ty, ..} => format!("Slice|{lo:?}|{hi:?}|{step:?}|{ty:?}"),
+        ArenaExpr::Broadcast {e, shape, ty} => format!("Broadcast|{e:?}|{shape:?}|{ty:?}"),
+    }
+}
+
+/// A side-table attaching analysis results (inferred `Type`, parallelization decisions, ...) to
+/// arena nodes without storing them inline on `ArenaExpr`.
+#[derive(Clone, Debug)]
+pub struct ArenaMap<T> {
+    entries: BTreeMap<ExprId, T>,
+}
+
+impl<T> ArenaMap<T> {
+    pub fn new() -> Self {
+        ArenaMap {entries: BTreeMap::new()}
+    }
+
+    pub fn insert(&mut self, id: ExprId, v: T) -> Option<T> {
+        self.entries.insert(id, v)
+    }
+
+    pub fn get(&self, id: ExprId) -> Option<&T> {
+        self.entries.get(&id)
+    }
+
+    pub fn contains(&self, id: ExprId) -> bool {
+        self.entries.contains_key(&id)
+    }
+}
+
+impl<T> Default for ArenaMap<T> {
+    fn default() -> Self {
+        ArenaMap::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::utils::info::Info;
+
+    fn int(v: i64) -> Expr {
+        Expr::Int {v, ty: Type::Tensor {sz: ElemSize::I64, shape: vec![], strides: None}, i: Info::default()}
+    }
+
+    fn add(l: Expr, r: Expr) -> Expr {
+        let ty = l.get_type().clone();
+        Expr::BinOp {lhs: Box::new(l), op: BinOp::Add, rhs: Box::new(r), ty, i: Info::default()}
+    }
+
+    #[test]
+    fn identical_subtrees_share_one_node() {
+        let mut arena = Arena::new();
+        let a = arena.intern(&add(int(1), int(2)));
+        let b = arena.intern(&add(int(1), int(2)));
+        assert_eq!(a, b);
+        // Structural sharing: the three-node tree (BinOp + two Int literals) interned twice
+        // leaves 3 distinct nodes, not 3 per interned copy times 2 copies.
+        assert_eq!(arena.len(), 3);
+    }
+
+    #[test]
+    fn different_subtrees_get_distinct_ids() {
+        let mut arena = Arena::new();
+        let a = arena.intern(&add(int(1), int(2)));
+        let b = arena.intern(&add(int(1), int(3)));
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn to_expr_roundtrips_a_distinct_tree() {
+        let mut arena = Arena::new();
+        let e = add(int(1), int(2));
+        let id = arena.intern(&e);
+        assert_eq!(arena.to_expr(id), e);
+    }
+
+    #[test]
+    fn arena_map_round_trips_a_value() {
+        let mut arena = Arena::new();
+        let id = arena.intern(&int(5));
+        let mut map: ArenaMap<&str> = ArenaMap::new();
+        assert!(!map.contains(id));
+        map.insert(id, "five");
+        assert_eq!(map.get(id), Some(&"five"));
+    }
+}
@@ -10,7 +10,8 @@ use std::fmt;
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, EnumIter)]
 pub enum ElemSize {
-    Bool, I8, I16, I32, I64, F16, F32, F64
+    Bool, I8, I16, I32, I64, U8, U16, U32, U64,
+    F16, BF16, TF32, F32, F64, Complex64, Complex128
 }
 
 impl ElemSize {
@@ -21,9 +22,24 @@ impl ElemSize {
         }
     }
 
+    pub fn is_unsigned_integer(&self) -> bool {
+        match self {
+            ElemSize::U8 | ElemSize::U16 | ElemSize::U32 | ElemSize::U64 => true,
+            _ => false
+        }
+    }
+
+    pub fn is_complex(&self) -> bool {
+        match self {
+            ElemSize::Complex64 | ElemSize::Complex128 => true,
+            _ => false
+        }
+    }
+
     pub fn is_floating_point(&self) -> bool {
         match self {
-            ElemSize::F16 | ElemSize::F32 | ElemSize::F64 => true,
+            ElemSize::F16 | ElemSize::BF16 | ElemSize::TF32 |
+            ElemSize::F32 | ElemSize::F64 => true,
             _ => false
         }
     }
@@ -37,9 +53,17 @@ impl fmt::Display for ElemSize {
             ElemSize::I16 => write!(f, "int16"),
             ElemSize::I32 => write!(f, "int32"),
             ElemSize::I64 => write!(f, "int64"),
+            ElemSize::U8 => write!(f, "uint8"),
+            ElemSize::U16 => write!(f, "uint16"),
+            ElemSize::U32 => write!(f, "uint32"),
+            ElemSize::U64 => write!(f, "uint64"),
             ElemSize::F16 => write!(f, "float16"),
+            ElemSize::BF16 => write!(f, "bfloat16"),
+            ElemSize::TF32 => write!(f, "tfloat32"),
             ElemSize::F32 => write!(f, "float32"),
             ElemSize::F64 => write!(f, "float64"),
+            ElemSize::Complex64 => write!(f, "complex64"),
+            ElemSize::Complex128 => write!(f, "complex128"),
         }
     }
 }
@@ -47,7 +71,7 @@ impl fmt::Display for ElemSize {
 #[derive(Clone, Debug, PartialEq)]
 pub enum Type {
     String,
-    Tensor {sz: ElemSize, shape: Vec<i64>},
+    Tensor {sz: ElemSize, shape: Vec<i64>, strides: Option<Vec<i64>>},
     Tuple {elems: Vec<Type>},
     Dict {fields: BTreeMap<String, Type>},
     Unknown
@@ -56,7 +80,7 @@ pub enum Type {
 impl Type {
     pub fn get_scalar_elem_size<'a>(&'a self) -> Option<&'a ElemSize> {
         match self {
-            Type::Tensor {sz, shape} if shape.len() == 0 => Some(sz),
+            Type::Tensor {sz, shape, ..} if shape.len() == 0 => Some(sz),
             _ => None
         }
     }
@@ -71,6 +95,16 @@ impl Type {
             .is_some_and(|sz| sz.is_signed_integer())
     }
 
+    pub fn is_unsigned_integer(&self) -> bool {
+        self.get_scalar_elem_size()
+            .is_some_and(|sz| sz.is_unsigned_integer())
+    }
+
+    pub fn is_complex(&self) -> bool {
+        self.get_scalar_elem_size()
+            .is_some_and(|sz| sz.is_complex())
+    }
+
     pub fn is_floating_point(&self) -> bool {
         self.get_scalar_elem_size()
             .is_some_and(|sz| sz.is_floating_point())
@@ -92,7 +126,7 @@ impl Ord for Type {
             (Type::String, _) => Ordering::Less,
             (Type::Tensor {..}, Type::String) =>
                 Ordering::Greater,
-            (Type::Tensor {sz: lsz, shape: lsh}, Type::Tensor {sz: rsz, shape: rsh}) => {
+            (Type::Tensor {sz: lsz, shape: lsh, ..}, Type::Tensor {sz: rsz, shape: rsh, ..}) => {
                 lsz.cmp(rsz).then(lsh.cmp(rsh))
             },
             (Type::Tensor {..}, _) => Ordering::Less,
@@ -126,8 +160,8 @@ impl fmt::Display for Type {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Type::String => write!(f, "string"),
-            Type::Tensor {sz, shape} if shape.is_empty() => write!(f, "{sz}"),
-            Type::Tensor {sz, shape} => {
+            Type::Tensor {sz, shape, ..} if shape.is_empty() => write!(f, "{sz}"),
+            Type::Tensor {sz, shape, ..} => {
                 let sh = shape.iter().map(|i| i.to_string()).join(",");
                 write!(f, "tensor<{sz}>[{sh}]")
             },
@@ -238,6 +272,8 @@ pub enum Expr {
     Dict {fields: BTreeMap<String, Expr>, ty: Type, i: Info},
     Builtin {func: Builtin, args: Vec<Expr>, ty: Type, i: Info},
     Convert {e: Box<Expr>, ty: Type},
+    Slice {lo: Option<Box<Expr>>, hi: Option<Box<Expr>>, step: Option<Box<Expr>>, ty: Type, i: Info},
+    Broadcast {e: Box<Expr>, shape: Vec<i64>, ty: Type},
 }
 
 impl Expr {
@@ -256,6 +292,8 @@ impl Expr {
             Expr::Dict {ty, ..} => ty,
             Expr::Builtin {ty, ..} => ty,
             Expr::Convert {ty, ..} => ty,
+            Expr::Slice {ty, ..} => ty,
+            Expr::Broadcast {ty, ..} => ty,
         }
     }
 
@@ -273,7 +311,9 @@ impl Expr {
             Expr::Tuple {..} => 9,
             Expr::Dict {..} => 10,
             Expr::Builtin {..} => 11,
-            Expr::Convert {..} => 12
+            Expr::Convert {..} => 12,
+            Expr::Slice {..} => 13,
+            Expr::Broadcast {..} => 14
         }
     }
 
@@ -291,7 +331,104 @@ impl Expr {
             Expr::Tuple {elems, ty, ..} => Expr::Tuple {elems, ty, i},
             Expr::Dict {fields, ty, ..} => Expr::Dict {fields, ty, i},
             Expr::Builtin {func, args, ty, ..} => Expr::Builtin {func, args, ty, i},
-            Expr::Convert {e, ty} => Expr::Convert {e: Box::new(e.with_info(i)), ty}
+            Expr::Convert {e, ty} => Expr::Convert {e: Box::new(e.with_info(i)), ty},
+            Expr::Slice {lo, hi, step, ty, ..} => Expr::Slice {lo, hi, step, ty, i},
+            Expr::Broadcast {e, shape, ty} => Expr::Broadcast {e: Box::new(e.with_info(i)), shape, ty}
+        }
+    }
+}
+
+/// Structural recursion over the immediate sub-expressions of a node. Implementing this trait once
+/// per AST type means a transform or analysis only has to match the variants it actually cares
+/// about and can delegate everything else to `map_children`/`fold_children`, rather than re-enumerating every
+/// `Expr`/`Stmt` variant by hand (as `get_type`, `with_info`, `Ord` and `Display` each do).
+pub trait MapChildren {
+    /// Rebuild the node, applying `f` to each immediate child expression.
+    fn map_children(self, f: impl FnMut(Expr) -> Expr) -> Self;
+
+    /// Accumulate a value over each immediate child expression.
+    fn fold_children<A>(&self, acc: A, f: impl FnMut(A, &Expr) -> A) -> A;
+}
+
+impl MapChildren for Expr {
+    fn map_children(self, mut f: impl FnMut(Expr) -> Expr) -> Self {
+        match self {
+            Expr::UnOp {op, arg, ty, i} =>
+                Expr::UnOp {op, arg: Box::new(f(*arg)), ty, i},
+            Expr::BinOp {lhs, op, rhs, ty, i} =>
+                Expr::BinOp {lhs: Box::new(f(*lhs)), op, rhs: Box::new(f(*rhs)), ty, i},
+            Expr::IfExpr {cond, thn, els, ty, i} =>
+                Expr::IfExpr {
+                    cond: Box::new(f(*cond)), thn: Box::new(f(*thn)), els: Box::new(f(*els)), ty, i
+                },
+            Expr::Subscript {target, idx, ty, i} =>
+                Expr::Subscript {target: Box::new(f(*target)), idx: Box::new(f(*idx)), ty, i},
+            Expr::Tuple {elems, ty, i} =>
+                Expr::Tuple {elems: elems.into_iter().map(f).collect(), ty, i},
+            Expr::Builtin {func, args, ty, i} =>
+                Expr::Builtin {func, args: args.into_iter().map(f).collect(), ty, i},
+            Expr::Dict {fields, ty, i} =>
+                Expr::Dict {fields: fields.into_iter().map(|(k, v)| (k, f(v))).collect(), ty, i},
+            Expr::Convert {e, ty} =>
+                Expr::Convert {e: Box::new(f(*e)), ty},
+            Expr::Broadcast {e, shape, ty} =>
+                Expr::Broadcast {e: Box::new(f(*e)), shape, ty},
+            Expr::Slice {lo, hi, step, ty, i} => {
+                let apply = |e: Option<Box<Expr>>, f: &mut dyn FnMut(Expr) -> Expr|
+                    e.map(|e| Box::new(f(*e)));
+                Expr::Slice {
+                    lo: apply(lo, &mut f), hi: apply(hi, &mut f), step: apply(step, &mut f), ty, i
+                }
+            },
+            e @ (Expr::Var {..} | Expr::String {..} | Expr::Bool {..} |
+                 Expr::Int {..} | Expr::Float {..}) => e,
+        }
+    }
+
+    fn fold_children<A>(&self, acc: A, mut f: impl FnMut(A, &Expr) -> A) -> A {
+        match self {
+            Expr::UnOp {arg, ..} => f(acc, arg),
+            Expr::BinOp {lhs, rhs, ..} => f(f(acc, lhs), rhs),
+            Expr::IfExpr {cond, thn, els, ..} => f(f(f(acc, cond), thn), els),
+            Expr::Subscript {target, idx, ..} => f(f(acc, target), idx),
+            Expr::Tuple {elems, ..} => elems.iter().fold(acc, f),
+            Expr::Builtin {args, ..} => args.iter().fold(acc, f),
+            Expr::Dict {fields, ..} => fields.values().fold(acc, f),
+            Expr::Convert {e, ..} => f(acc, e),
+            Expr::Broadcast {e, ..} => f(acc, e),
+            Expr::Slice {lo, hi, step, ..} =>
+                [lo, hi, step].into_iter().flatten().fold(acc, |acc, e| f(acc, e)),
+            Expr::Var {..} | Expr::String {..} | Expr::Bool {..} |
+            Expr::Int {..} | Expr::Float {..} => acc,
+        }
+    }
+}
+
+impl MapChildren for Stmt {
+    fn map_children(self, mut f: impl FnMut(Expr) -> Expr) -> Self {
+        match self {
+            Stmt::Definition {ty, id, expr, i} =>
+                Stmt::Definition {ty, id, expr: f(expr), i},
+            Stmt::Assign {dst, expr, i} =>
+                Stmt::Assign {dst: f(dst), expr: f(expr), i},
+            Stmt::For {var, lo, hi, step, body, i} =>
+                Stmt::For {var, lo: f(lo), hi: f(hi), step, body, i},
+            Stmt::If {cond, thn, els, i} =>
+                Stmt::If {cond: f(cond), thn, els, i},
+            Stmt::While {cond, body, i} =>
+                Stmt::While {cond: f(cond), body, i},
+            s @ (Stmt::WithGpuContext {..} | Stmt::Label {..}) => s,
+        }
+    }
+
+    fn fold_children<A>(&self, acc: A, mut f: impl FnMut(A, &Expr) -> A) -> A {
+        match self {
+            Stmt::Definition {expr, ..} => f(acc, expr),
+            Stmt::Assign {dst, expr, ..} => f(f(acc, dst), expr),
+            Stmt::For {lo, hi, ..} => f(f(acc, lo), hi),
+            Stmt::If {cond, ..} => f(acc, cond),
+            Stmt::While {cond, ..} => f(acc, cond),
+            Stmt::WithGpuContext {..} | Stmt::Label {..} => acc,
         }
     }
 }
@@ -324,6 +461,12 @@ impl Ord for Expr {
                 lfunc.cmp(rfunc).then(largs.cmp(rargs)),
             (Expr::Convert {e: le, ty: lty}, Expr::Convert {e: re, ty: rty}) =>
                 le.cmp(re).then(lty.cmp(rty)),
+            ( Expr::Broadcast {e: le, shape: lsh, ..}
+            , Expr::Broadcast {e: re, shape: rsh, ..} ) =>
+                le.cmp(re).then(lsh.cmp(rsh)),
+            ( Expr::Slice {lo: llo, hi: lhi, step: lstep, ..}
+            , Expr::Slice {lo: rlo, hi: rhi, step: rstep, ..} ) =>
+                llo.cmp(rlo).then(lhi.cmp(rhi)).then(lstep.cmp(rstep)),
             (lhs, rhs) => lhs.discriminator().cmp(&rhs.discriminator())
         }
     }
@@ -374,6 +517,17 @@ impl fmt::Display for Expr {
             Expr::Convert {e, ty} => {
                 write!(f, "({ty}){e}")
             },
+            Expr::Slice {lo, hi, step, ..} => {
+                let part = |e: &Option<Box<Expr>>| match e {
+                    Some(e) => format!("{e}"),
+                    None => "".to_string()
+                };
+                write!(f, "{0}:{1}:{2}", part(lo), part(hi), part(step))
+            },
+            Expr::Broadcast {e, shape, ..} => {
+                let sh = shape.iter().map(|i| i.to_string()).join(",");
+                write!(f, "broadcast({e}, [{sh}])")
+            },
         }
     }
 }
@@ -394,6 +548,8 @@ impl InfoNode for Expr {
             Expr::Dict {i, ..} => i.clone(),
             Expr::Builtin {i, ..} => i.clone(),
             Expr::Convert {e, ..} => e.get_info(),
+            Expr::Slice {i, ..} => i.clone(),
+            Expr::Broadcast {e, ..} => e.get_info(),
         }
     }
 }
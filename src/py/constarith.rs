@@ -0,0 +1,74 @@
+//! Integer constant-arithmetic shared by the const-folding passes (`fold`, `simplify`, and
+//! `type_check`'s bounds-checking `constfold`), so that a given constant expression folds to the
+//! same value no matter which pass reaches it first.
+//!
+//! In particular, `//` and `%` must match Python's floor-division semantics (the quotient rounds
+//! toward negative infinity and the remainder takes the sign of the divisor), which is neither
+//! Rust's `/`/`%` (truncates toward zero) nor `div_euclid`/`rem_euclid` (remainder is always
+//! non-negative). All three disagree with each other and with Python for negative operands.
+
+/// Python-style floor division at `i128` precision, where it cannot overflow for any `i64`
+/// operand pair. Panics if `b == 0`, as Rust's own `/` does; callers are expected to check for
+/// division by zero themselves, since they each report it differently (a compile error or a
+/// declined fold).
+pub fn floor_div(a: i128, b: i128) -> i128 {
+    let q = a / b;
+    let r = a % b;
+    if r != 0 && (r < 0) != (b < 0) { q - 1 } else { q }
+}
+
+/// Python-style floor modulo at `i128` precision: `a == floor_div(a, b) * b + floor_mod(a, b)`,
+/// with the result taking the sign of `b`. Panics if `b == 0`.
+pub fn floor_mod(a: i128, b: i128) -> i128 {
+    let r = a % b;
+    if r != 0 && (r < 0) != (b < 0) { r + b } else { r }
+}
+
+/// `floor_div` for `i64` operands, returning `None` for division by zero or for the one case that
+/// does not fit back into an `i64` (`i64::MIN / -1`).
+pub fn checked_floor_div(a: i64, b: i64) -> Option<i64> {
+    if b == 0 {
+        None
+    } else {
+        i64::try_from(floor_div(a as i128, b as i128)).ok()
+    }
+}
+
+/// `floor_mod` for `i64` operands, returning `None` for division by zero. Unlike division, the
+/// result always fits in an `i64` (its magnitude is strictly less than `|b|`).
+pub fn checked_floor_mod(a: i64, b: i64) -> Option<i64> {
+    if b == 0 {
+        None
+    } else {
+        Some(floor_mod(a as i128, b as i128) as i64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn matches_python_floor_division() {
+        assert_eq!(floor_div(-7, -2), 3);
+        assert_eq!(floor_mod(-7, -2), -1);
+        assert_eq!(floor_div(-7, 2), -4);
+        assert_eq!(floor_mod(-7, 2), 1);
+        assert_eq!(floor_div(7, -2), -4);
+        assert_eq!(floor_mod(7, -2), -1);
+        assert_eq!(floor_div(7, 2), 3);
+        assert_eq!(floor_mod(7, 2), 1);
+    }
+
+    #[test]
+    fn checked_floor_div_rejects_the_i64_overflow_case() {
+        assert_eq!(checked_floor_div(i64::MIN, -1), None);
+        assert_eq!(checked_floor_div(1, 0), None);
+    }
+
+    #[test]
+    fn checked_floor_mod_never_overflows() {
+        assert_eq!(checked_floor_mod(i64::MIN, -1), Some(0));
+        assert_eq!(checked_floor_mod(1, 0), None);
+    }
+}
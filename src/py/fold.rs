@@ -0,0 +1,297 @@
+use crate::py_type_error;
+use crate::utils::err::*;
+use crate::utils::info::*;
+use super::ast::*;
+use super::constarith;
+
+/// A folded scalar constant. The const-evaluation pass reduces constant subtrees to one of these
+/// before rewriting the node as a literal of its checked type.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConstVal {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl ConstVal {
+    /// Materialize the value as a literal expression, keeping the checked `Type` of the folded
+    /// node so the rewrite is type-preserving.
+    fn to_expr(self, ty: Type, i: Info) -> Expr {
+        match self {
+            ConstVal::Int(v) => Expr::Int {v, ty, i},
+            ConstVal::Float(v) => Expr::Float {v, ty, i},
+            ConstVal::Bool(v) => Expr::Bool {v, ty, i},
+        }
+    }
+
+    fn as_f64(&self) -> f64 {
+        match self {
+            ConstVal::Int(v) => *v as f64,
+            ConstVal::Float(v) => *v,
+            ConstVal::Bool(v) => if *v { 1.0 } else { 0.0 }
+        }
+    }
+}
+
+/// Evaluate a constant subtree, returning `Ok(None)` for anything that is not a compile-time
+/// constant (any subtree containing a `Var`, `Subscript`, or non-constant builtin), `Ok(Some(_))`
+/// for a folded value, and `Err` for a detectable compile-time error such as division by zero or
+/// integer overflow.
+pub fn eval_const(e: &Expr) -> PyResult<Option<ConstVal>> {
+    let i = e.get_info();
+    match e {
+        Expr::Int {v, ..} => Ok(Some(ConstVal::Int(*v))),
+        Expr::Float {v, ..} => Ok(Some(ConstVal::Float(*v))),
+        Expr::Bool {v, ..} => Ok(Some(ConstVal::Bool(*v))),
+        Expr::UnOp {op, arg, ..} => {
+            match eval_const(arg)? {
+                Some(v) => eval_unop(op, v, &i),
+                None => Ok(None)
+            }
+        },
+        Expr::BinOp {lhs, op, rhs, ..} => {
+            match (eval_const(lhs)?, eval_const(rhs)?) {
+                (Some(l), Some(r)) => eval_binop(op, l, r, &i),
+                _ => Ok(None)
+            }
+        },
+        Expr::Convert {e, ty} => {
+            match eval_const(e)? {
+                Some(v) => Ok(Some(eval_convert(v, ty))),
+                None => Ok(None)
+            }
+        },
+        Expr::Builtin {func, args, ..} => eval_builtin(func, args, &i),
+        // Conservatively leave anything else (variables, subscripts, tuples, ...) untouched.
+        _ => Ok(None)
+    }
+}
+
+fn eval_unop(op: &UnOp, v: ConstVal, i: &Info) -> PyResult<Option<ConstVal>> {
+    match (op, v) {
+        (UnOp::Sub, ConstVal::Int(v)) => match v.checked_neg() {
+            Some(v) => Ok(Some(ConstVal::Int(v))),
+            None => py_type_error!(i, "Integer overflow while folding unary minus")
+        },
+        (UnOp::Sub, ConstVal::Float(v)) => Ok(Some(ConstVal::Float(-v))),
+        (UnOp::Not, ConstVal::Bool(v)) => Ok(Some(ConstVal::Bool(!v))),
+        (UnOp::BitNeg, ConstVal::Int(v)) => Ok(Some(ConstVal::Int(!v))),
+        _ => Ok(None)
+    }
+}
+
+fn eval_binop(op: &BinOp, l: ConstVal, r: ConstVal, i: &Info) -> PyResult<Option<ConstVal>> {
+    // Promote operands the same way the type checker does: if either operand is a float the whole
+    // operation is evaluated in floating point.
+    match (l, r) {
+        (ConstVal::Int(l), ConstVal::Int(r)) => eval_int_binop(op, l, r, i),
+        (ConstVal::Bool(l), ConstVal::Bool(r)) => Ok(match op {
+            BinOp::And => Some(ConstVal::Bool(l && r)),
+            BinOp::Or => Some(ConstVal::Bool(l || r)),
+            BinOp::Eq => Some(ConstVal::Bool(l == r)),
+            BinOp::Neq => Some(ConstVal::Bool(l != r)),
+            _ => None
+        }),
+        _ => Ok(eval_float_binop(op, l.as_f64(), r.as_f64()))
+    }
+}
+
+fn eval_int_binop(op: &BinOp, l: i64, r: i64, i: &Info) -> PyResult<Option<ConstVal>> {
+    let overflow = || py_type_error!(i, "Integer overflow in constant expression");
+    let divzero = || py_type_error!(i, "Division by zero in constant expression");
+    Ok(Some(match op {
+        BinOp::Add => ConstVal::Int(l.checked_add(r).ok_or(()).or_else(|_| overflow())?),
+        BinOp::Sub => ConstVal::Int(l.checked_sub(r).ok_or(()).or_else(|_| overflow())?),
+        BinOp::Mul => ConstVal::Int(l.checked_mul(r).ok_or(()).or_else(|_| overflow())?),
+        // Python floor division rounds the quotient toward negative infinity and gives the
+        // remainder the sign of the divisor, which `div_euclid`/`rem_euclid` (remainder always
+        // non-negative) and Rust's own `/`/`%` (truncate toward zero) both disagree with for
+        // negative operands.
+        BinOp::FloorDiv => {
+            if r == 0 { return divzero(); }
+            match constarith::checked_floor_div(l, r) {
+                Some(v) => ConstVal::Int(v),
+                None => return overflow()
+            }
+        },
+        BinOp::Mod => {
+            if r == 0 { return divzero(); }
+            match constarith::checked_floor_mod(l, r) {
+                Some(v) => ConstVal::Int(v),
+                None => return overflow()
+            }
+        },
+        BinOp::BitAnd => ConstVal::Int(l & r),
+        BinOp::BitOr => ConstVal::Int(l | r),
+        BinOp::BitXor => ConstVal::Int(l ^ r),
+        // A raw `<<`/`>>` panics (debug) or gives an unspecified result (release) for a shift
+        // amount outside `0..64`; decline to fold rather than crash on a valid program whose
+        // shift amount we cannot fold safely.
+        BinOp::BitShl => match u32::try_from(r).ok().and_then(|r| l.checked_shl(r)) {
+            Some(v) => ConstVal::Int(v),
+            None => return Ok(None)
+        },
+        BinOp::BitShr => match u32::try_from(r).ok().and_then(|r| l.checked_shr(r)) {
+            Some(v) => ConstVal::Int(v),
+            None => return Ok(None)
+        },
+        BinOp::Eq => ConstVal::Bool(l == r),
+        BinOp::Neq => ConstVal::Bool(l != r),
+        BinOp::Leq => ConstVal::Bool(l <= r),
+        BinOp::Geq => ConstVal::Bool(l >= r),
+        BinOp::Lt => ConstVal::Bool(l < r),
+        BinOp::Gt => ConstVal::Bool(l > r),
+        _ => return Ok(None)
+    }))
+}
+
+fn eval_float_binop(op: &BinOp, l: f64, r: f64) -> Option<ConstVal> {
+    match op {
+        BinOp::Add => Some(ConstVal::Float(l + r)),
+        BinOp::Sub => Some(ConstVal::Float(l - r)),
+        BinOp::Mul => Some(ConstVal::Float(l * r)),
+        BinOp::Div => Some(ConstVal::Float(l / r)),
+        BinOp::Pow => Some(ConstVal::Float(l.powf(r))),
+        BinOp::Eq => Some(ConstVal::Bool(l == r)),
+        BinOp::Neq => Some(ConstVal::Bool(l != r)),
+        BinOp::Leq => Some(ConstVal::Bool(l <= r)),
+        BinOp::Geq => Some(ConstVal::Bool(l >= r)),
+        BinOp::Lt => Some(ConstVal::Bool(l < r)),
+        BinOp::Gt => Some(ConstVal::Bool(l > r)),
+        _ => None
+    }
+}
+
+fn eval_convert(v: ConstVal, ty: &Type) -> ConstVal {
+    match ty.get_scalar_elem_size() {
+        Some(sz) if sz.is_floating_point() => ConstVal::Float(v.as_f64()),
+        Some(ElemSize::Bool) => ConstVal::Bool(v.as_f64() != 0.0),
+        Some(_) => ConstVal::Int(match v {
+            ConstVal::Int(v) => v,
+            ConstVal::Float(v) => v as i64,
+            ConstVal::Bool(v) => v as i64
+        }),
+        None => v
+    }
+}
+
+fn eval_builtin(func: &Builtin, args: &Vec<Expr>, i: &Info) -> PyResult<Option<ConstVal>> {
+    let vals = args.iter()
+        .map(eval_const)
+        .collect::<PyResult<Option<Vec<ConstVal>>>>()?;
+    let vals = match vals {
+        Some(vals) => vals,
+        None => return Ok(None)
+    };
+    let f = |k: usize| vals[k].as_f64();
+    Ok(match (func, vals.len()) {
+        (Builtin::Exp, 1) => Some(ConstVal::Float(f(0).exp())),
+        (Builtin::Log, 1) => Some(ConstVal::Float(f(0).ln())),
+        (Builtin::Sqrt, 1) => Some(ConstVal::Float(f(0).sqrt())),
+        (Builtin::Cos, 1) => Some(ConstVal::Float(f(0).cos())),
+        (Builtin::Sin, 1) => Some(ConstVal::Float(f(0).sin())),
+        (Builtin::Tanh, 1) => Some(ConstVal::Float(f(0).tanh())),
+        (Builtin::Abs, 1) => match vals[0] {
+            ConstVal::Int(v) => Some(ConstVal::Int(v.abs())),
+            v => Some(ConstVal::Float(v.as_f64().abs()))
+        },
+        (Builtin::Max, 2) => fold_minmax(vals[0], vals[1], true),
+        (Builtin::Min, 2) => fold_minmax(vals[0], vals[1], false),
+        (Builtin::Atan2, 2) => Some(ConstVal::Float(f(0).atan2(f(1)))),
+        _ => None
+    })
+}
+
+fn fold_minmax(l: ConstVal, r: ConstVal, max: bool) -> Option<ConstVal> {
+    match (l, r) {
+        (ConstVal::Int(l), ConstVal::Int(r)) =>
+            Some(ConstVal::Int(if max { l.max(r) } else { l.min(r) })),
+        _ => {
+            let (l, r) = (l.as_f64(), r.as_f64());
+            Some(ConstVal::Float(if max { l.max(r) } else { l.min(r) }))
+        }
+    }
+}
+
+/// Fold an expression tree bottom-up, rewriting every constant subtree into a single literal of
+/// its checked type. Subtrees containing a variable, subscript, or non-constant builtin are left
+/// structurally intact (only their constant children are folded).
+pub fn fold_expr(e: Expr) -> PyResult<Expr> {
+    let ty = e.get_type().clone();
+    let i = e.get_info();
+    // Fold the children first, then attempt to fold the (now partially-folded) node itself.
+    let e = match e {
+        Expr::UnOp {op, arg, ty, i} => Expr::UnOp {op, arg: Box::new(fold_expr(*arg)?), ty, i},
+        Expr::BinOp {lhs, op, rhs, ty, i} =>
+            Expr::BinOp {lhs: Box::new(fold_expr(*lhs)?), op, rhs: Box::new(fold_expr(*rhs)?), ty, i},
+        Expr::IfExpr {cond, thn, els, ty, i} =>
+            Expr::IfExpr {
+                cond: Box::new(fold_expr(*cond)?),
+                thn: Box::new(fold_expr(*thn)?),
+                els: Box::new(fold_expr(*els)?),
+                ty, i
+            },
+        Expr::Subscript {target, idx, ty, i} =>
+            Expr::Subscript {target: Box::new(fold_expr(*target)?), idx: Box::new(fold_expr(*idx)?), ty, i},
+        Expr::Tuple {elems, ty, i} => {
+            let elems = elems.into_iter().map(fold_expr).collect::<PyResult<Vec<Expr>>>()?;
+            Expr::Tuple {elems, ty, i}
+        },
+        Expr::Builtin {func, args, ty, i} => {
+            let args = args.into_iter().map(fold_expr).collect::<PyResult<Vec<Expr>>>()?;
+            Expr::Builtin {func, args, ty, i}
+        },
+        Expr::Dict {fields, ty, i} => {
+            let fields = fields.into_iter()
+                .map(|(k, v)| Ok((k, fold_expr(v)?)))
+                .collect::<PyResult<std::collections::BTreeMap<String, Expr>>>()?;
+            Expr::Dict {fields, ty, i}
+        },
+        Expr::Convert {e, ty} => Expr::Convert {e: Box::new(fold_expr(*e)?), ty},
+        Expr::Broadcast {e, shape, ty} => Expr::Broadcast {e: Box::new(fold_expr(*e)?), shape, ty},
+        Expr::Slice {lo, hi, step, ty, i} => {
+            let bound = |b: Option<Box<Expr>>| b.map(|e| fold_expr(*e).map(Box::new)).transpose();
+            Expr::Slice {lo: bound(lo)?, hi: bound(hi)?, step: bound(step)?, ty, i}
+        },
+        e => e
+    };
+    match eval_const(&e)? {
+        Some(v) => Ok(v.to_expr(ty, i)),
+        None => Ok(e)
+    }
+}
+
+fn fold_stmt(stmt: Stmt) -> PyResult<Stmt> {
+    match stmt {
+        Stmt::Definition {ty, id, expr, i} =>
+            Ok(Stmt::Definition {ty, id, expr: fold_expr(expr)?, i}),
+        Stmt::Assign {dst, expr, i} =>
+            Ok(Stmt::Assign {dst: fold_expr(dst)?, expr: fold_expr(expr)?, i}),
+        // Folding the loop bounds makes the trip count statically known when possible.
+        Stmt::For {var, lo, hi, step, body, i} =>
+            Ok(Stmt::For {var, lo: fold_expr(lo)?, hi: fold_expr(hi)?, step, body: fold_stmts(body)?, i}),
+        Stmt::If {cond, thn, els, i} =>
+            Ok(Stmt::If {cond: fold_expr(cond)?, thn: fold_stmts(thn)?, els: fold_stmts(els)?, i}),
+        Stmt::While {cond, body, i} =>
+            Ok(Stmt::While {cond: fold_expr(cond)?, body: fold_stmts(body)?, i}),
+        Stmt::WithGpuContext {body, i} =>
+            Ok(Stmt::WithGpuContext {body: fold_stmts(body)?, i}),
+        Stmt::Label {label, assoc, i} => {
+            let assoc = match assoc {
+                Some(s) => Some(Box::new(fold_stmt(*s)?)),
+                None => None
+            };
+            Ok(Stmt::Label {label, assoc, i})
+        }
+    }
+}
+
+fn fold_stmts(stmts: Vec<Stmt>) -> PyResult<Vec<Stmt>> {
+    stmts.into_iter().map(fold_stmt).collect()
+}
+
+/// Run the constant-folding pass over a type-checked function body. This should run after
+/// `type_check_body` so that every node carries a concrete type.
+pub fn constant_fold_body(body: Vec<Stmt>) -> PyResult<Vec<Stmt>> {
+    fold_stmts(body)
+}
@@ -0,0 +1,276 @@
+use crate::utils::info::*;
+use crate::utils::name::Name;
+use super::ast::*;
+
+use std::collections::BTreeMap;
+
+/// A single problem found while inferring types for a whole function. Unlike the forward type
+/// checker, the inference pass does not abort on the first problem; it accumulates every
+/// diagnostic (both unresolved expressions and outright mismatches) so the Python front-end can
+/// report them all in one go.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TypeDiagnostic {
+    pub info: Info,
+    pub message: String,
+}
+
+/// A fresh type variable, allocated for every expression and for every program variable whose type
+/// is not yet pinned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct TyVar(usize);
+
+/// A union-find based solver over type variables. Each variable belongs to an equivalence class;
+/// a class may additionally be pinned to a concrete `Type`. Unifying two classes merges them, and
+/// pinning a class to an incompatible concrete type records a mismatch rather than failing.
+struct Solver {
+    parent: Vec<usize>,
+    ty: Vec<Option<Type>>,
+    names: BTreeMap<Name, TyVar>,
+    first_use: BTreeMap<Name, Info>,
+    mismatches: Vec<TypeDiagnostic>,
+}
+
+impl Solver {
+    fn new() -> Self {
+        Solver {
+            parent: vec![], ty: vec![], names: BTreeMap::new(),
+            first_use: BTreeMap::new(), mismatches: vec![]
+        }
+    }
+
+    fn fresh(&mut self) -> TyVar {
+        let v = self.parent.len();
+        self.parent.push(v);
+        self.ty.push(None);
+        TyVar(v)
+    }
+
+    fn find(&mut self, TyVar(x): TyVar) -> TyVar {
+        let mut x = x;
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        TyVar(x)
+    }
+
+    /// A type variable standing for the program variable `id`, shared across all of its uses.
+    /// Records `i` as the variable's span the first time it is seen, so a diagnostic about `id` can
+    /// point at its earliest occurrence rather than nowhere in particular.
+    fn var_of(&mut self, id: &Name, i: &Info) -> TyVar {
+        self.first_use.entry(id.clone()).or_insert_with(|| i.clone());
+        match self.names.get(id) {
+            Some(v) => *v,
+            None => {
+                let v = self.fresh();
+                self.names.insert(id.clone(), v);
+                v
+            }
+        }
+    }
+
+    /// Pin `v` to the concrete type `t`, recording a mismatch if `v` is already pinned to an
+    /// incompatible type.
+    fn pin(&mut self, v: TyVar, t: Type, i: &Info) {
+        let TyVar(root) = self.find(v);
+        match &self.ty[root] {
+            Some(existing) if !unifiable(existing, &t) => {
+                self.mismatches.push(TypeDiagnostic {
+                    info: i.clone(),
+                    message: format!("Conflicting types {existing} and {t}")
+                });
+            },
+            Some(_) => {},
+            None => self.ty[root] = Some(t)
+        }
+    }
+
+    fn unify(&mut self, a: TyVar, b: TyVar, i: &Info) {
+        let TyVar(ra) = self.find(a);
+        let TyVar(rb) = self.find(b);
+        if ra == rb {
+            return;
+        }
+        match (self.ty[ra].clone(), self.ty[rb].clone()) {
+            (Some(ta), Some(tb)) if !unifiable(&ta, &tb) => {
+                self.mismatches.push(TypeDiagnostic {
+                    info: i.clone(),
+                    message: format!("Conflicting types {ta} and {tb}")
+                });
+            },
+            (None, Some(t)) => self.ty[ra] = Some(t),
+            _ => {}
+        }
+        self.parent[rb] = ra;
+    }
+
+    fn resolved(&mut self, v: TyVar) -> Option<Type> {
+        let TyVar(root) = self.find(v);
+        self.ty[root].clone()
+    }
+}
+
+/// Two concrete types can unify if they are equal or are both scalars (the forward checker decides
+/// the exact least-upper-bound later; inference only needs compatibility).
+fn unifiable(l: &Type, r: &Type) -> bool {
+    l == r || (l.get_scalar_elem_size().is_some() && r.get_scalar_elem_size().is_some())
+}
+
+/// Infer a type variable for `e`, adding the equality constraints implied by its structure.
+fn infer_expr(s: &mut Solver, e: &Expr) -> TyVar {
+    let i = e.get_info();
+    let v = s.fresh();
+    match e {
+        Expr::Var {id, ty, ..} => {
+            let vid = s.var_of(id, &i);
+            s.unify(v, vid, &i);
+            if ty != &Type::Unknown {
+                s.pin(v, ty.clone(), &i);
+            }
+        },
+        Expr::Bool {..} | Expr::Int {..} | Expr::Float {..} | Expr::String {..} => {
+            if e.get_type() != &Type::Unknown {
+                s.pin(v, e.get_type().clone(), &i);
+            }
+        },
+        // Binary operators require their operands to share a type, which also becomes the result
+        // type for every operator except the comparisons.
+        Expr::BinOp {lhs, op, rhs, ..} => {
+            let l = infer_expr(s, lhs);
+            let r = infer_expr(s, rhs);
+            s.unify(l, r, &i);
+            let is_comparison = matches!(op,
+                BinOp::Eq | BinOp::Neq | BinOp::Leq | BinOp::Geq | BinOp::Lt | BinOp::Gt);
+            if !is_comparison {
+                s.unify(v, l, &i);
+            }
+        },
+        _ => {
+            // For the remaining variants we still descend into the children so their constraints
+            // (and any pinned types) are collected, but we do not relate the result type.
+            e.fold_children((), |(), c| { infer_expr(s, c); });
+        }
+    }
+    v
+}
+
+fn infer_stmt(s: &mut Solver, stmt: &Stmt) {
+    match stmt {
+        Stmt::Definition {id, expr, ..} => {
+            let e = infer_expr(s, expr);
+            let i = stmt.get_info();
+            let vid = s.var_of(id, &i);
+            s.unify(vid, e, &i);
+        },
+        Stmt::Assign {dst, expr, i} => {
+            let d = infer_expr(s, dst);
+            let e = infer_expr(s, expr);
+            s.unify(d, e, i);
+        },
+        Stmt::For {var, lo, hi, body, i, ..} => {
+            infer_expr(s, lo);
+            infer_expr(s, hi);
+            let v = s.var_of(var, i);
+            s.pin(v, Type::Tensor {sz: ElemSize::I64, shape: vec![], strides: None}, i);
+            infer_stmts(s, body);
+        },
+        Stmt::If {cond, thn, els, ..} => {
+            infer_expr(s, cond);
+            infer_stmts(s, thn);
+            infer_stmts(s, els);
+        },
+        Stmt::While {cond, body, ..} => {
+            infer_expr(s, cond);
+            infer_stmts(s, body);
+        },
+        Stmt::WithGpuContext {body, ..} => infer_stmts(s, body),
+        Stmt::Label {assoc, ..} => {
+            if let Some(assoc) = assoc {
+                infer_stmt(s, assoc);
+            }
+        }
+    }
+}
+
+fn infer_stmts(s: &mut Solver, stmts: &Vec<Stmt>) {
+    for stmt in stmts {
+        infer_stmt(s, stmt);
+    }
+}
+
+/// Walk the whole function, inferring the type of every variable and expression using union-find,
+/// and return the complete list of diagnostics: every program variable whose type variable remains
+/// unresolved (with the `Info` span of a use) followed by every accumulated mismatch. An empty
+/// result means the function is fully resolvable. This parallels `type_check` but never returns on
+/// the first error, so the front-end can report all missing annotations at once.
+pub fn type_check_diagnostics(def: &FunDef, params: &Vec<Param>) -> Vec<TypeDiagnostic> {
+    let mut s = Solver::new();
+    for Param {id, ty, i} in params {
+        let v = s.var_of(id, i);
+        if ty != &Type::Unknown {
+            s.pin(v, ty.clone(), i);
+        }
+    }
+    infer_stmts(&mut s, &def.body);
+
+    // Report any variable whose type could not be determined, using the span of its earliest use.
+    let mut unresolved = vec![];
+    let names = s.names.clone();
+    for (id, v) in names {
+        if s.resolved(v).is_none() {
+            let i = s.first_use.get(&id)
+                .cloned()
+                .unwrap_or_else(|| panic!("Parir internal error: variable {id} has no recorded use"));
+            unresolved.push(TypeDiagnostic {
+                info: i,
+                message: format!("Could not infer a type for variable {id}")
+            });
+        }
+    }
+    unresolved.extend(s.mismatches.clone());
+    unresolved
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn scalar(sz: ElemSize) -> Type {
+        Type::Tensor {sz, shape: vec![], strides: None}
+    }
+
+    fn var(s: &str) -> Name {
+        Name::new(s.to_string())
+    }
+
+    fn fun(body: Vec<Stmt>) -> FunDef {
+        FunDef {id: var("f"), params: vec![], body, i: Info::default()}
+    }
+
+    #[test]
+    fn resolves_through_parameter_type() {
+        // `y = x` with `x` a parameter of known type resolves `y`, although neither `y` nor the
+        // use of `x` carries a type annotation.
+        let params = vec![Param {id: var("x"), ty: scalar(ElemSize::F32), i: Info::default()}];
+        let body = vec![Stmt::Definition {
+            ty: Type::Unknown,
+            id: var("y"),
+            expr: Expr::Var {id: var("x"), ty: Type::Unknown, i: Info::default()},
+            i: Info::default()
+        }];
+        let diags = type_check_diagnostics(&fun(body), &params);
+        assert!(diags.is_empty());
+    }
+
+    #[test]
+    fn reports_unresolved_variable() {
+        let body = vec![Stmt::Definition {
+            ty: Type::Unknown,
+            id: var("y"),
+            expr: Expr::Var {id: var("x"), ty: Type::Unknown, i: Info::default()},
+            i: Info::default()
+        }];
+        let diags = type_check_diagnostics(&fun(body), &vec![]);
+        assert!(!diags.is_empty());
+    }
+}
@@ -0,0 +1,238 @@
+use crate::utils::info::*;
+use super::arena::Arena;
+use super::ast::*;
+use super::constarith;
+
+/// Simplify an expression tree bottom-up before code generation so that GPU kernels do not carry
+/// dead arithmetic. The pass performs plain constant folding (evaluating operations whose operands
+/// are all literals) as well as a collection of algebraic identity and annihilator laws (e.g.
+/// `x + 0 -> x`, `x * 0 -> 0`). Because every `Expr` variant in this IR is side-effect free, these
+/// rewrites are always sound.
+///
+/// Each subtree is iterated to a fixpoint, so a rewrite that exposes a new literal (such as folding
+/// one side of an addition) is picked up by a subsequent pass. The fixpoint check interns both
+/// sides into a scratch `Arena` and compares the resulting `ExprId`s rather than deep-comparing
+/// the two `Expr` trees; interning still walks each tree once, and because the arena's key leaves
+/// out `Info`, two trees that differ only in `Info` count as unchanged.
+pub fn simplify_expr(e: Expr) -> Expr {
+    let mut e = simplify_children(e);
+    let mut arena = Arena::new();
+    loop {
+        let next = simplify_once(e.clone());
+        let e_id = arena.intern(&e);
+        let next_id = arena.intern(&next);
+        if next_id == e_id {
+            break next;
+        }
+        e = next;
+    }
+}
+
+fn simplify_children(e: Expr) -> Expr {
+    match e {
+        Expr::UnOp {op, arg, ty, i} => {
+            let arg = Box::new(simplify_expr(*arg));
+            Expr::UnOp {op, arg, ty, i}
+        },
+        Expr::BinOp {lhs, op, rhs, ty, i} => {
+            let lhs = Box::new(simplify_expr(*lhs));
+            let rhs = Box::new(simplify_expr(*rhs));
+            Expr::BinOp {lhs, op, rhs, ty, i}
+        },
+        Expr::IfExpr {cond, thn, els, ty, i} => {
+            let cond = Box::new(simplify_expr(*cond));
+            let thn = Box::new(simplify_expr(*thn));
+            let els = Box::new(simplify_expr(*els));
+            Expr::IfExpr {cond, thn, els, ty, i}
+        },
+        Expr::Subscript {target, idx, ty, i} => {
+            let target = Box::new(simplify_expr(*target));
+            let idx = Box::new(simplify_expr(*idx));
+            Expr::Subscript {target, idx, ty, i}
+        },
+        Expr::Tuple {elems, ty, i} => {
+            let elems = elems.into_iter().map(simplify_expr).collect();
+            Expr::Tuple {elems, ty, i}
+        },
+        Expr::Builtin {func, args, ty, i} => {
+            let args = args.into_iter().map(simplify_expr).collect();
+            Expr::Builtin {func, args, ty, i}
+        },
+        Expr::Dict {fields, ty, i} => {
+            let fields = fields.into_iter()
+                .map(|(k, v)| (k, simplify_expr(v)))
+                .collect();
+            Expr::Dict {fields, ty, i}
+        },
+        Expr::Convert {e, ty} => Expr::Convert {e: Box::new(simplify_expr(*e)), ty},
+        Expr::Broadcast {e, shape, ty} =>
+            Expr::Broadcast {e: Box::new(simplify_expr(*e)), shape, ty},
+        e => e
+    }
+}
+
+/// A scalar literal payload, used to evaluate an operation whose operands are all literals.
+enum Lit {
+    Int(i64),
+    Float(f64),
+    Bool(bool)
+}
+
+fn as_lit(e: &Expr) -> Option<Lit> {
+    match e {
+        Expr::Int {v, ..} => Some(Lit::Int(*v)),
+        Expr::Float {v, ..} => Some(Lit::Float(*v)),
+        Expr::Bool {v, ..} => Some(Lit::Bool(*v)),
+        _ => None
+    }
+}
+
+fn int_lit(v: i64, ty: Type, i: Info) -> Expr {
+    Expr::Int {v, ty, i}
+}
+
+fn float_lit(v: f64, ty: Type, i: Info) -> Expr {
+    Expr::Float {v, ty, i}
+}
+
+fn bool_lit(v: bool, ty: Type, i: Info) -> Expr {
+    Expr::Bool {v, ty, i}
+}
+
+fn is_int_zero(e: &Expr) -> bool {
+    matches!(e, Expr::Int {v: 0, ..})
+}
+
+fn is_int_one(e: &Expr) -> bool {
+    matches!(e, Expr::Int {v: 1, ..})
+}
+
+fn is_true(e: &Expr) -> bool {
+    matches!(e, Expr::Bool {v: true, ..})
+}
+
+fn is_false(e: &Expr) -> bool {
+    matches!(e, Expr::Bool {v: false, ..})
+}
+
+/// Some binary operators are commutative; canonicalizing the operand order lets a literal migrate
+/// to a single side so that the identity and folding rules only need to match one shape.
+fn is_commutative(op: &BinOp) -> bool {
+    matches!(op,
+        BinOp::Add | BinOp::Mul | BinOp::And | BinOp::Or |
+        BinOp::BitAnd | BinOp::BitOr | BinOp::BitXor | BinOp::Eq | BinOp::Neq)
+}
+
+fn fold_binop(op: &BinOp, lhs: &Expr, rhs: &Expr, ty: &Type, i: &Info) -> Option<Expr> {
+    let l = as_lit(lhs)?;
+    let r = as_lit(rhs)?;
+    let ty = ty.clone();
+    let i = i.clone();
+    match (l, r) {
+        (Lit::Int(l), Lit::Int(r)) => match op {
+            BinOp::Add => Some(int_lit(l.wrapping_add(r), ty, i)),
+            BinOp::Sub => Some(int_lit(l.wrapping_sub(r), ty, i)),
+            BinOp::Mul => Some(int_lit(l.wrapping_mul(r), ty, i)),
+            BinOp::FloorDiv => constarith::checked_floor_div(l, r).map(|v| int_lit(v, ty, i)),
+            BinOp::Mod => constarith::checked_floor_mod(l, r).map(|v| int_lit(v, ty, i)),
+            BinOp::BitAnd => Some(int_lit(l & r, ty, i)),
+            BinOp::BitOr => Some(int_lit(l | r, ty, i)),
+            BinOp::BitXor => Some(int_lit(l ^ r, ty, i)),
+            BinOp::Eq => Some(bool_lit(l == r, ty, i)),
+            BinOp::Neq => Some(bool_lit(l != r, ty, i)),
+            BinOp::Leq => Some(bool_lit(l <= r, ty, i)),
+            BinOp::Geq => Some(bool_lit(l >= r, ty, i)),
+            BinOp::Lt => Some(bool_lit(l < r, ty, i)),
+            BinOp::Gt => Some(bool_lit(l > r, ty, i)),
+            _ => None
+        },
+        (Lit::Float(l), Lit::Float(r)) => match op {
+            BinOp::Add => Some(float_lit(l + r, ty, i)),
+            BinOp::Sub => Some(float_lit(l - r, ty, i)),
+            BinOp::Mul => Some(float_lit(l * r, ty, i)),
+            BinOp::Div => Some(float_lit(l / r, ty, i)),
+            BinOp::Pow => Some(float_lit(l.powf(r), ty, i)),
+            BinOp::Eq => Some(bool_lit(l == r, ty, i)),
+            BinOp::Neq => Some(bool_lit(l != r, ty, i)),
+            BinOp::Leq => Some(bool_lit(l <= r, ty, i)),
+            BinOp::Geq => Some(bool_lit(l >= r, ty, i)),
+            BinOp::Lt => Some(bool_lit(l < r, ty, i)),
+            BinOp::Gt => Some(bool_lit(l > r, ty, i)),
+            _ => None
+        },
+        (Lit::Bool(l), Lit::Bool(r)) => match op {
+            BinOp::And => Some(bool_lit(l && r, ty, i)),
+            BinOp::Or => Some(bool_lit(l || r, ty, i)),
+            BinOp::Eq => Some(bool_lit(l == r, ty, i)),
+            BinOp::Neq => Some(bool_lit(l != r, ty, i)),
+            _ => None
+        },
+        _ => None
+    }
+}
+
+fn fold_unop(op: &UnOp, arg: &Expr, ty: &Type, i: &Info) -> Option<Expr> {
+    let ty = ty.clone();
+    let i = i.clone();
+    match (op, as_lit(arg)?) {
+        (UnOp::Sub, Lit::Int(v)) => Some(int_lit(v.wrapping_neg(), ty, i)),
+        (UnOp::Sub, Lit::Float(v)) => Some(float_lit(-v, ty, i)),
+        (UnOp::Not, Lit::Bool(v)) => Some(bool_lit(!v, ty, i)),
+        (UnOp::BitNeg, Lit::Int(v)) => Some(int_lit(!v, ty, i)),
+        _ => None
+    }
+}
+
+fn simplify_once(e: Expr) -> Expr {
+    match e {
+        Expr::UnOp {op, arg, ty, i} => {
+            if let Some(folded) = fold_unop(&op, &arg, &ty, &i) {
+                folded
+            } else {
+                Expr::UnOp {op, arg, ty, i}
+            }
+        },
+        Expr::BinOp {lhs, op, rhs, ty, i} => {
+            // Canonicalize commutative operators so a literal operand ends up on the right-hand
+            // side, letting the identity rules below match a single shape.
+            let (lhs, rhs) = if is_commutative(&op) && as_lit(&lhs).is_some() && as_lit(&rhs).is_none() {
+                (rhs, lhs)
+            } else {
+                (lhs, rhs)
+            };
+            if let Some(folded) = fold_binop(&op, &lhs, &rhs, &ty, &i) {
+                return folded;
+            }
+            // The zero/one-producing rewrites below replace the whole node with a freshly built
+            // `Expr::Int`, which is only well-typed (and, for `x - x` and `x * 0`, only sound) when
+            // `ty` is itself an integer type: for floats, `NaN - NaN` and `Inf * 0` are not `0`, and
+            // an `Expr::Int` carrying a float `Type` would be a malformed node either way.
+            let is_int_ty = ty.is_signed_integer() || ty.is_unsigned_integer();
+            match op {
+                // Additive identities
+                BinOp::Add | BinOp::Sub if is_int_zero(&rhs) => lhs.with_info(i),
+                BinOp::Add if is_int_zero(&lhs) => rhs.with_info(i),
+                // Multiplicative identity and annihilator
+                BinOp::Mul if is_int_one(&rhs) => lhs.with_info(i),
+                BinOp::Mul if is_int_one(&lhs) => rhs.with_info(i),
+                BinOp::Mul if is_int_ty && is_int_zero(&rhs) => int_lit(0, ty, i),
+                BinOp::Mul if is_int_ty && is_int_zero(&lhs) => int_lit(0, ty, i),
+                BinOp::Div | BinOp::FloorDiv if is_int_one(&rhs) => lhs.with_info(i),
+                // Subtraction of an expression from itself
+                BinOp::Sub if is_int_ty && lhs == rhs => int_lit(0, ty, i),
+                // Power identities
+                BinOp::Pow if is_int_one(&rhs) => lhs.with_info(i),
+                BinOp::Pow if is_int_ty && is_int_zero(&rhs) => int_lit(1, ty, i),
+                // Boolean identities
+                BinOp::And if is_true(&rhs) => lhs.with_info(i),
+                BinOp::And if is_true(&lhs) => rhs.with_info(i),
+                BinOp::And if is_false(&rhs) || is_false(&lhs) => bool_lit(false, ty, i),
+                BinOp::Or if is_false(&rhs) => lhs.with_info(i),
+                BinOp::Or if is_false(&lhs) => rhs.with_info(i),
+                BinOp::Or if is_true(&rhs) || is_true(&lhs) => bool_lit(true, ty, i),
+                _ => Expr::BinOp {lhs, op, rhs, ty, i}
+            }
+        },
+        e => e
+    }
+}
@@ -3,6 +3,7 @@ use crate::utils::err::*;
 use crate::utils::info::*;
 use crate::utils::name::Name;
 use super::ast::*;
+use super::constarith;
 
 use pyo3::PyTypeInfo;
 use pyo3::prelude::*;
@@ -25,8 +26,22 @@ fn compile_elem_size<'py>(dtype: Bound<'py, PyAny>) -> PyResult<ElemSize> {
         Ok(ElemSize::I32)
     } else if dtype.eq(torch.getattr("int64")?)? {
         Ok(ElemSize::I64)
+    } else if dtype.eq(torch.getattr("uint8")?)? {
+        Ok(ElemSize::U8)
+    } else if dtype.eq(torch.getattr("uint16")?)? {
+        Ok(ElemSize::U16)
+    } else if dtype.eq(torch.getattr("uint32")?)? {
+        Ok(ElemSize::U32)
+    } else if dtype.eq(torch.getattr("uint64")?)? {
+        Ok(ElemSize::U64)
+    } else if dtype.eq(torch.getattr("complex64")?)? {
+        Ok(ElemSize::Complex64)
+    } else if dtype.eq(torch.getattr("complex128")?)? {
+        Ok(ElemSize::Complex128)
     } else if dtype.eq(torch.getattr("float16")?)? {
         Ok(ElemSize::F16)
+    } else if dtype.eq(torch.getattr("bfloat16")?)? {
+        Ok(ElemSize::BF16)
     } else if dtype.eq(torch.getattr("float32")?)? {
         Ok(ElemSize::F32)
     } else if dtype.eq(torch.getattr("float64")?)? {
@@ -57,11 +72,11 @@ fn convert_type<'py>(arg: &Bound<'py, PyAny>) -> PyResult<Type> {
         let dtype = arg.getattr("dtype")?;
         let sz = compile_elem_size(dtype)?;
         let shape = get_tensor_shape(&arg)?;
-        Ok(Type::Tensor {sz, shape})
+        Ok(Type::Tensor {sz, shape, strides: None})
     } else if arg.is_instance(&PyInt::type_object(arg.py()))? {
-        Ok(Type::Tensor {sz: ElemSize::I64, shape: vec![]})
+        Ok(Type::Tensor {sz: ElemSize::I64, shape: vec![], strides: None})
     } else if arg.is_instance(&PyFloat::type_object(arg.py()))? {
-        Ok(Type::Tensor {sz: ElemSize::F64, shape: vec![]})
+        Ok(Type::Tensor {sz: ElemSize::F64, shape: vec![], strides: None})
     } else if arg.is_instance(&PyDict::type_object(arg.py()))? {
         let fields = arg.call_method0("items")?
             .try_iter()?
@@ -106,18 +121,302 @@ fn lub_elem_size(
         (ElemSize::I32, ElemSize::I8 | ElemSize::I16) => Ok(ElemSize::I32),
         (ElemSize::I32, _) if rhs.is_signed_integer() => Ok(rhs.clone()),
         (ElemSize::I64, _) if rhs.is_signed_integer() => Ok(lhs.clone()),
+        // Unsigned integers widen within the unsigned family to the larger of the two.
+        (l, r) if l.is_unsigned_integer() && r.is_unsigned_integer() => {
+            Ok(if int_bits(l) >= int_bits(r) { l.clone() } else { r.clone() })
+        },
+        // Mixing a signed and an unsigned integer promotes to a signed type wide enough to hold
+        // both, matching PyTorch's promotion rules (so `int8`/`uint8` promote to `int16`).
+        (l, r) if (l.is_signed_integer() && r.is_unsigned_integer())
+               || (l.is_unsigned_integer() && r.is_signed_integer()) => {
+            promote_mixed_int(l, r, i)
+        },
+        // Complex only unifies with complex, widening complex64 to complex128.
+        (l, r) if l.is_complex() && r.is_complex() => {
+            if l == &ElemSize::Complex128 || r == &ElemSize::Complex128 {
+                Ok(ElemSize::Complex128)
+            } else {
+                Ok(ElemSize::Complex64)
+            }
+        },
+        // bfloat16 and the tensor-float accumulation format both widen to float32 when combined
+        // with float16, since neither can represent the other's mantissa without loss.
+        (ElemSize::F16, ElemSize::BF16 | ElemSize::TF32) => Ok(ElemSize::F32),
         (ElemSize::F16, _) if rhs.is_floating_point() => Ok(rhs.clone()),
-        (ElemSize::F32, ElemSize::F16) => Ok(ElemSize::F32),
+        (ElemSize::BF16, ElemSize::BF16) => Ok(ElemSize::BF16),
+        (ElemSize::BF16, ElemSize::F16 | ElemSize::TF32) => Ok(ElemSize::F32),
+        (ElemSize::BF16, _) if rhs.is_floating_point() => Ok(rhs.clone()),
+        (ElemSize::TF32, ElemSize::TF32) => Ok(ElemSize::TF32),
+        (ElemSize::TF32, ElemSize::F16 | ElemSize::BF16) => Ok(ElemSize::F32),
+        (ElemSize::TF32, _) if rhs.is_floating_point() => Ok(rhs.clone()),
+        (ElemSize::F32, ElemSize::F16 | ElemSize::BF16 | ElemSize::TF32) => Ok(ElemSize::F32),
         (ElemSize::F32, _) if rhs.is_floating_point() => Ok(rhs.clone()),
         (ElemSize::F64, _) if rhs.is_floating_point() => Ok(lhs.clone()),
+        // Mixing an integer and a floating-point element size promotes to the float, matching how
+        // numeric languages coerce operands. A 64-bit integer widens the result to float64 so its
+        // precision is not silently lost when combined with a narrower float.
+        (l, r) if (is_integer(l) && r.is_floating_point())
+               || (l.is_floating_point() && is_integer(r)) => {
+            let (int_sz, float_sz) = if is_integer(l) { (l, r) } else { (r, l) };
+            if int_bits(int_sz) >= 64 && float_sz != &ElemSize::F64 {
+                Ok(ElemSize::F64)
+            } else {
+                Ok(float_sz.clone())
+            }
+        },
         _ => py_type_error!(i, "Incompatible element types")
     }
 }
 
+/// Whether an element size is an integer of either signedness.
+fn is_integer(sz: &ElemSize) -> bool {
+    sz.is_signed_integer() || sz.is_unsigned_integer()
+}
+
 fn compatible_elem_types(lhs: &ElemSize, rhs: &ElemSize) -> bool {
     lub_elem_size(lhs, rhs, &Info::default()).is_ok()
 }
 
+/// The width in bits of an integer element size (signed or unsigned), or `0` for non-integers.
+fn int_bits(sz: &ElemSize) -> u32 {
+    match sz {
+        ElemSize::I8 | ElemSize::U8 => 8,
+        ElemSize::I16 | ElemSize::U16 => 16,
+        ElemSize::I32 | ElemSize::U32 => 32,
+        ElemSize::I64 | ElemSize::U64 => 64,
+        _ => 0
+    }
+}
+
+/// Promote a mixed signed/unsigned integer pair to the signed type that can represent both values.
+/// If the signed operand is already wider than the unsigned one it is kept as-is; otherwise the
+/// result widens to the next signed width above the unsigned operand.
+fn promote_mixed_int(lhs: &ElemSize, rhs: &ElemSize, i: &Info) -> PyResult<ElemSize> {
+    let (signed, unsigned) = if lhs.is_signed_integer() { (lhs, rhs) } else { (rhs, lhs) };
+    if int_bits(signed) > int_bits(unsigned) {
+        Ok(signed.clone())
+    } else {
+        match int_bits(unsigned) {
+            8 => Ok(ElemSize::I16),
+            16 => Ok(ElemSize::I32),
+            32 => Ok(ElemSize::I64),
+            _ => py_type_error!(i, "No signed integer type wide enough to hold {lhs} and {rhs}")
+        }
+    }
+}
+
+/// Broadcast two tensor shapes following the NumPy rule: align the shapes by their trailing
+/// dimension, conceptually left-padding the shorter with 1s, and for each aligned pair the
+/// dimensions are compatible iff they are equal, one of them is 1, or one of them is the dynamic
+/// dimension `-1` (which is compatible with anything). The output dimension is the larger of the
+/// pair (or dynamic if either input is dynamic).
+fn broadcast_shapes(lsh: &Vec<i64>, rsh: &Vec<i64>, i: &Info) -> PyResult<Vec<i64>> {
+    let n = lsh.len().max(rsh.len());
+    let mut shape = Vec::with_capacity(n);
+    for k in 1..=n {
+        let dl = if k <= lsh.len() { lsh[lsh.len() - k] } else { 1 };
+        let dr = if k <= rsh.len() { rsh[rsh.len() - k] } else { 1 };
+        let d = if dl == -1 || dr == -1 {
+            -1
+        } else if dl == dr || dr == 1 {
+            dl
+        } else if dl == 1 {
+            dr
+        } else {
+            let ls = lsh.iter().map(|d| d.to_string()).join(",");
+            let rs = rsh.iter().map(|d| d.to_string()).join(",");
+            return py_type_error!(i, "Cannot broadcast incompatible shapes [{ls}] and [{rs}]");
+        };
+        shape.push(d);
+    }
+    shape.reverse();
+    Ok(shape)
+}
+
+fn shapes_broadcast_compatible(lsh: &Vec<i64>, rsh: &Vec<i64>) -> bool {
+    broadcast_shapes(lsh, rsh, &Info::default()).is_ok()
+}
+
+/// Record that a tensor operand is virtually repeated to `shape` by wrapping it in an
+/// `Expr::Broadcast` (analogous to the `Expr::Convert` that indexing inserts), so later passes know
+/// which axes are broadcast. Operands that already have the target shape, and non-tensor operands,
+/// are returned unchanged.
+fn broadcast_operand(e: Expr, shape: &Vec<i64>) -> Expr {
+    if let Type::Tensor {sz, shape: esh, ..} = e.get_type() {
+        if esh != shape {
+            let ty = Type::Tensor {sz: sz.clone(), shape: shape.clone(), strides: None};
+            return Expr::Broadcast {e: Box::new(e), shape: shape.clone(), ty};
+        }
+    }
+    e
+}
+
+/// The row-major (C-contiguous) strides of a tensor of the given shape, measured in elements. A
+/// dynamic dimension (`-1`) poisons the strides of all outer axes, which are then reported as
+/// dynamic as well.
+fn contiguous_strides(shape: &Vec<i64>) -> Vec<i64> {
+    let mut strides = vec![1i64; shape.len()];
+    for k in (0..shape.len().saturating_sub(1)).rev() {
+        let outer = shape[k + 1];
+        strides[k] = if outer < 0 || strides[k + 1] < 0 { -1 } else { strides[k + 1] * outer };
+    }
+    strides
+}
+
+/// The extent of a sliced axis of static size `n`, given statically-known bounds. For a positive
+/// step the output length is `ceil((hi - lo) / step)` with absent bounds defaulting to `0`/`n`; for
+/// a negative step it is `ceil((lo - hi) / -step)` with absent bounds defaulting to `n-1`/`-1`. The
+/// bounds are clamped to the valid range before the length is computed. A dynamic axis (`n == -1`)
+/// or a zero step yields a dynamic extent `-1`. A `None` bound is an absent one and takes the
+/// default; a non-literal bound is turned into a dynamic extent by the caller, which then does not
+/// call this function.
+fn slice_extent(n: i64, lo: Option<i64>, hi: Option<i64>, step: Option<i64>) -> i64 {
+    let step = step.unwrap_or(1);
+    if n < 0 || step == 0 {
+        return -1;
+    }
+    if step > 0 {
+        let lo = lo.unwrap_or(0).clamp(0, n);
+        let hi = hi.unwrap_or(n).clamp(0, n);
+        if hi <= lo { 0 } else { (hi - lo + step - 1) / step }
+    } else if n == 0 {
+        // `(0, n - 1)` is an invalid (empty) clamp range when `n == 0`; a zero-length axis simply
+        // has no elements for any negative-step slice to select.
+        0
+    } else {
+        let s = -step;
+        let lo = lo.unwrap_or(n - 1).clamp(0, n - 1);
+        let hi = hi.unwrap_or(-1).clamp(-1, n - 1);
+        if lo <= hi { 0 } else { (lo - hi + s - 1) / s }
+    }
+}
+
+fn const_int(e: &Expr) -> Option<i64> {
+    match e {
+        Expr::Int {v, ..} => Some(*v),
+        _ => None
+    }
+}
+
+/// A tiny constant-evaluation helper used to reason about statically-known tensor indices. It folds
+/// literal arithmetic (`+`, `-`, `*`, `//` and `%` on integers; `+`, `-`, `*`, `/` and `**` once a
+/// float is involved; plus `UnOp::Sub`) into a single value; comparisons and bitwise operators are
+/// not folded. It returns `None` for anything that references a variable,
+/// subscript, or otherwise cannot be evaluated at compile time. Folding never changes observable
+/// runtime behavior because it only ever touches literal operands.
+mod constfold {
+    use super::*;
+
+    /// A folded scalar constant. Integers are kept in `i128` so intermediate products cannot
+    /// silently wrap before a bounds comparison.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum Const {
+        Int(i128),
+        Float(f64),
+    }
+
+    pub fn fold(e: &Expr) -> Option<Const> {
+        match e {
+            Expr::Int {v, ..} => Some(Const::Int(*v as i128)),
+            Expr::Float {v, ..} => Some(Const::Float(*v)),
+            Expr::UnOp {op: UnOp::Sub, arg, ..} => match fold(arg)? {
+                Const::Int(v) => Some(Const::Int(-v)),
+                Const::Float(v) => Some(Const::Float(-v))
+            },
+            Expr::BinOp {lhs, op, rhs, ..} => fold_binop(op, fold(lhs)?, fold(rhs)?),
+            Expr::Convert {e, ..} => fold(e),
+            _ => None
+        }
+    }
+
+    /// Fold an expression that must evaluate to an integer constant, returning `None` for
+    /// non-constant or floating-point results.
+    pub fn fold_int(e: &Expr) -> Option<i128> {
+        match fold(e)? {
+            Const::Int(v) => Some(v),
+            Const::Float(_) => None
+        }
+    }
+
+    fn fold_binop(op: &BinOp, l: Const, r: Const) -> Option<Const> {
+        match (l, r) {
+            (Const::Int(l), Const::Int(r)) => match op {
+                BinOp::Add => Some(Const::Int(l + r)),
+                BinOp::Sub => Some(Const::Int(l - r)),
+                BinOp::Mul => Some(Const::Int(l * r)),
+                // Python floor division, not div_euclid/rem_euclid (remainder always non-negative)
+                // or truncating `/`/`%` — see super::constarith for why they disagree.
+                BinOp::FloorDiv if r != 0 => Some(Const::Int(constarith::floor_div(l, r))),
+                BinOp::Mod if r != 0 => Some(Const::Int(constarith::floor_mod(l, r))),
+                _ => None
+            },
+            (l, r) => {
+                let (l, r) = (as_f64(l), as_f64(r));
+                match op {
+                    BinOp::Add => Some(Const::Float(l + r)),
+                    BinOp::Sub => Some(Const::Float(l - r)),
+                    BinOp::Mul => Some(Const::Float(l * r)),
+                    BinOp::Div => Some(Const::Float(l / r)),
+                    BinOp::Pow => Some(Const::Float(l.powf(r))),
+                    _ => None
+                }
+            }
+        }
+    }
+
+    fn as_f64(c: Const) -> f64 {
+        match c {
+            Const::Int(v) => v as f64,
+            Const::Float(v) => v
+        }
+    }
+}
+
+/// Fold and bounds-check the constant integer indices of a subscript against the statically-known
+/// axes of the target tensor. A concrete index `k` for an axis of static extent `n` must satisfy
+/// `-n <= k < n`; a negative index is normalized to `n + k`. Non-constant indices and dynamic axes
+/// are left untouched.
+fn bounds_check_index(shape: &Vec<i64>, idx: Expr, i: &Info) -> PyResult<Expr> {
+    match idx {
+        Expr::Tuple {elems, ty, i: ti} => {
+            let elems = elems.into_iter()
+                .enumerate()
+                .map(|(axis, e)| bounds_check_axis(shape.get(axis).copied(), e, i))
+                .collect::<PyResult<Vec<Expr>>>()?;
+            Ok(Expr::Tuple {elems, ty, i: ti})
+        },
+        e => bounds_check_axis(shape.first().copied(), e, i)
+    }
+}
+
+fn bounds_check_axis(n: Option<i64>, e: Expr, i: &Info) -> PyResult<Expr> {
+    match (n, constfold::fold_int(&e)) {
+        (Some(n), Some(k)) if n >= 0 => {
+            let n = n as i128;
+            if k >= n || k < -n {
+                return py_type_error!(i, "Index {k} is out of bounds for axis of size {n}");
+            }
+            let v = if k < 0 { (n + k) as i64 } else { k as i64 };
+            Ok(Expr::Int {v, ty: e.get_type().clone(), i: e.get_info()})
+        },
+        _ => Ok(e)
+    }
+}
+
+fn type_check_slice_bound(
+    vars: &BTreeMap<Name, Type>,
+    e: Option<Box<Expr>>
+) -> PyResult<Option<Box<Expr>>> {
+    match e {
+        Some(e) => {
+            let e = type_check_expr(vars, *e)?;
+            let e = ensure_scalar_type(e, ElemSize::I64)?;
+            Ok(Some(Box::new(e)))
+        },
+        None => Ok(None)
+    }
+}
+
 fn ensure_scalar_type(e: Expr, expected: ElemSize) -> PyResult<Expr> {
     let i = e.get_info();
     let ty = e.get_type();
@@ -128,7 +427,7 @@ fn ensure_scalar_type(e: Expr, expected: ElemSize) -> PyResult<Expr> {
             if actual.eq(&expected) {
                 Ok(e)
             } else {
-                Ok(Expr::Convert {e: Box::new(e), ty: Type::Tensor {sz: expected, shape: vec![]}})
+                Ok(Expr::Convert {e: Box::new(e), ty: Type::Tensor {sz: expected, shape: vec![], strides: None}})
             }
         } else {
             py_type_error!(i, "Expected element of type {expected}, found incompatible element type {actual}")
@@ -155,10 +454,12 @@ fn coerce_type(e: Expr, expected: &Type) -> PyResult<Expr> {
         let i = e.get_info();
         let actual = e.get_type();
         match (actual, expected) {
-            (Type::Tensor {sz: lsz, shape: lsh}, Type::Tensor {sz: rsz, shape: rsh}) => {
+            (Type::Tensor {sz: lsz, shape: lsh, ..}, Type::Tensor {sz: rsz, shape: rsh, ..}) => {
                 if lsh.len() == 0 && rsh.len() == 0 {
                     ensure_scalar_type(e, rsz.clone())
-                } else if lsz == rsz && lsh == rsh {
+                } else if compatible_elem_types(lsz, rsz) && shapes_broadcast_compatible(lsh, rsh) {
+                    // The operand broadcasts to the expected shape; downstream codegen handles the
+                    // repeated axes, so we accept it without inserting a conversion.
                     Ok(e)
                 } else {
                     py_type_error!(i, "Cannot coerce incompatible tensor types ({actual} != {expected})")
@@ -189,11 +490,13 @@ fn coerce_type(e: Expr, expected: &Type) -> PyResult<Expr> {
 /// size that is larger than or equal to that of both arguments. For instance, the least upper
 /// bound of an int16 and an int32 is int32.
 fn lub_type(l: Type, r: Type, i: &Info) -> PyResult<Type> {
-    match (l.get_scalar_elem_size(), r.get_scalar_elem_size()) {
-        (Some(lsz), Some(rsz)) => {
-            Ok(Type::Tensor {sz: lub_elem_size(lsz, rsz, i)?, shape: vec![]})
+    match (&l, &r) {
+        (Type::Tensor {sz: lsz, shape: lsh, ..}, Type::Tensor {sz: rsz, shape: rsh, ..}) => {
+            let sz = lub_elem_size(lsz, rsz, i)?;
+            let shape = broadcast_shapes(lsh, rsh, i)?;
+            Ok(Type::Tensor {sz, shape, strides: None})
         },
-        (None, None) if l.eq(&r) => Ok(l),
+        _ if l.eq(&r) => Ok(l),
         _ => py_type_error!(i, "Cannot unify incompatible types {l} and {r}"),
     }
 }
@@ -206,7 +509,7 @@ fn type_check_builtin(
     match &func {
         // Literals
         Builtin::Inf if args.is_empty() => {
-            Ok(Expr::Builtin {func, args, ty: Type::Tensor {sz: ElemSize::F64, shape: vec![]}, i})
+            Ok(Expr::Builtin {func, args, ty: Type::Tensor {sz: ElemSize::F64, shape: vec![], strides: None}, i})
         },
         // Unary operations on (floating-point) scalar values
         Builtin::Exp | Builtin::Log | Builtin::Cos | Builtin::Sin |
@@ -230,7 +533,8 @@ fn type_check_builtin(
         },
         Builtin::Abs if args.len() == 1 => {
             let ty = args[0].get_type().clone();
-            if ty.is_signed_integer() || ty.is_floating_point() {
+            // For unsigned integers abs is the identity, but it is still well-typed.
+            if ty.is_signed_integer() || ty.is_unsigned_integer() || ty.is_floating_point() {
                 Ok(Expr::Builtin {func, args, ty, i})
             } else {
                 py_type_error!(i, "Unexpected type {ty} of abs builtin")
@@ -243,7 +547,7 @@ fn type_check_builtin(
             if ty.get_scalar_elem_size().is_some() {
                 Ok(Expr::Convert {
                     e: Box::new(arg),
-                    ty: Type::Tensor {sz: sz.clone(), shape: vec![]}
+                    ty: Type::Tensor {sz: sz.clone(), shape: vec![], strides: None}
                 })
             } else {
                 py_type_error!(i, "Unexpected type {ty} of type conversion")
@@ -296,7 +600,7 @@ fn type_check_unop(
             }
         }
         UnOp::BitNeg => {
-            if ty.is_signed_integer() {
+            if ty.is_signed_integer() || ty.is_unsigned_integer() {
                 Ok(ty.clone())
             } else {
                 py_type_error!(i, "Invalid type {ty} of bitwise negation")
@@ -316,18 +620,40 @@ fn type_check_binop(
     let ty = lub_type(lty, rty, i)?;
     let lhs = coerce_type(lhs, &ty)?;
     let rhs = coerce_type(rhs, &ty)?;
+    // When an operand was broadcast to a wider shape, wrap it so codegen can replay the repeated
+    // axes rather than assuming the operand already has the result shape.
+    let (lhs, rhs) = if let Type::Tensor {shape, ..} = &ty {
+        (broadcast_operand(lhs, shape), broadcast_operand(rhs, shape))
+    } else {
+        (lhs, rhs)
+    };
+    // The arithmetic and bitwise checks look at the element size, so broadcast tensor operands
+    // are accepted as well as scalars.
+    let elem = if let Type::Tensor {sz, ..} = &ty { Some(sz.clone()) } else { None };
+    let is_signed = elem.as_ref().is_some_and(|sz| sz.is_signed_integer());
+    let is_unsigned = elem.as_ref().is_some_and(|sz| sz.is_unsigned_integer());
+    let is_float = elem.as_ref().is_some_and(|sz| sz.is_floating_point());
     let ty = match op {
-        // Arithmetic operations supporting either integers or floating point numbers
-        BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div => {
-            if ty.is_signed_integer() || ty.is_floating_point() {
+        // Arithmetic operations supporting either integers or floating point numbers. Subtraction
+        // is excluded here because it is not well-defined on pure unsigned integers (it may
+        // underflow), so it is handled separately below.
+        BinOp::Add | BinOp::Mul | BinOp::Div => {
+            if is_signed || is_unsigned || is_float {
                 Ok(ty)
             } else {
                 py_type_error!(i, "Invalid type {ty} of arithmetic operation")
             }
         },
+        BinOp::Sub => {
+            if is_signed || is_float {
+                Ok(ty)
+            } else {
+                py_type_error!(i, "Invalid type {ty} of subtraction")
+            }
+        },
         // Arithmetic operations only supported for integers
         BinOp::FloorDiv | BinOp::Mod => {
-            if ty.is_signed_integer() {
+            if is_signed || is_unsigned {
                 Ok(ty)
             } else {
                 py_type_error!(i, "Invalid type {ty} of integer arithmetic operation")
@@ -350,7 +676,7 @@ fn type_check_binop(
         },
         // Bitwise operations
         BinOp::BitAnd | BinOp::BitOr | BinOp::BitXor | BinOp::BitShl | BinOp::BitShr => {
-            if ty.is_signed_integer() {
+            if is_signed || is_unsigned {
                 Ok(ty)
             } else {
                 py_type_error!(i, "Invalid type {ty} of bitwise operation")
@@ -358,8 +684,8 @@ fn type_check_binop(
         },
         // Boolean comparison operations, allowing comparison between elementary types
         BinOp::Eq | BinOp::Neq | BinOp::Leq | BinOp::Geq | BinOp::Lt | BinOp::Gt => {
-            if let Some(_) = ty.get_scalar_elem_size() {
-                Ok(Type::Tensor {sz: ElemSize::Bool, shape: vec![]})
+            if let Type::Tensor {shape, ..} = &ty {
+                Ok(Type::Tensor {sz: ElemSize::Bool, shape: shape.clone(), strides: None})
             } else {
                 py_type_error!(i, "Invalid type {ty} of boolean comparison operation")
             }
@@ -382,13 +708,13 @@ fn type_check_expr(
         },
         Expr::String {v, i, ..} => Ok(Expr::String {v, ty: Type::String, i}),
         Expr::Bool {v, i, ..} => {
-            let ty = Type::Tensor {sz: ElemSize::Bool, shape: vec![]};
+            let ty = Type::Tensor {sz: ElemSize::Bool, shape: vec![], strides: None};
             Ok(Expr::Bool {v, ty, i})
         },
         Expr::Int {v, i, ..} =>
-            Ok(Expr::Int {v, ty: Type::Tensor {sz: ElemSize::I64, shape: vec![]}, i}),
+            Ok(Expr::Int {v, ty: Type::Tensor {sz: ElemSize::I64, shape: vec![], strides: None}, i}),
         Expr::Float {v, i, ..} =>
-            Ok(Expr::Float {v, ty: Type::Tensor {sz: ElemSize::F64, shape: vec![]}, i}),
+            Ok(Expr::Float {v, ty: Type::Tensor {sz: ElemSize::F64, shape: vec![], strides: None}, i}),
         Expr::UnOp {op, arg, i, ..} => {
             let arg = Box::new(type_check_expr(vars, *arg)?);
             let ty = type_check_unop(&op, &arg, &i)?;
@@ -433,7 +759,52 @@ fn type_check_expr(
                 },
                 idx => {
                     let idx = type_check_expr(vars, idx)?;
-                    let elem_ty = if let Type::Tensor {sz, shape} = target.get_type() {
+                    // Projecting an element out of a tuple requires a compile-time constant index,
+                    // since the element types are heterogeneous and cannot share one static type.
+                    if let Type::Tuple {elems} = target.get_type() {
+                        let n = elems.len() as i128;
+                        let ty = match constfold::fold_int(&idx) {
+                            Some(k) => {
+                                let norm = if k < 0 { k + n } else { k };
+                                if norm < 0 || norm >= n {
+                                    py_type_error!(i, "Tuple index {k} is out of range for tuple of length {0}", elems.len())?
+                                }
+                                elems[norm as usize].clone()
+                            },
+                            None => py_type_error!(i, "Tuple index must be a compile-time constant integer")?
+                        };
+                        return Ok(Expr::Subscript {
+                            target: Box::new(target), idx: Box::new(idx), ty, i
+                        });
+                    }
+                    // Slice indices (and index tuples that contain a slice) retain the sliced axes,
+                    // so they are handled separately from the integer-index path below.
+                    let slice_entries = match &idx {
+                        Expr::Slice {..} => Some(vec![idx.clone()]),
+                        Expr::Tuple {elems, ..}
+                            if elems.iter().any(|e| matches!(e, Expr::Slice {..})) =>
+                            Some(elems.clone()),
+                        _ => None
+                    };
+                    if let Some(entries) = slice_entries {
+                        let ty = if let Type::Tensor {sz, shape, strides} = target.get_type() {
+                            let base = strides.clone()
+                                .unwrap_or_else(|| contiguous_strides(shape));
+                            type_check_tensor_index(sz, shape, &base, &entries, &i)?
+                        } else {
+                            py_type_error!(i, "Subscript operation on unsupported target {target}")?
+                        };
+                        return Ok(Expr::Subscript {
+                            target: Box::new(target), idx: Box::new(idx), ty, i
+                        });
+                    }
+                    // Fold constant integer indices and reject statically out-of-bounds accesses.
+                    let idx = if let Type::Tensor {shape, ..} = target.get_type() {
+                        bounds_check_index(&shape.clone(), idx, &i)?
+                    } else {
+                        idx
+                    };
+                    let elem_ty = if let Type::Tensor {sz, shape, ..} = target.get_type() {
                         let idx_dims = match idx.get_type() {
                             Type::Tensor {shape, ..} if shape.len() == 0 => Ok(1),
                             Type::Tuple {elems} => Ok(elems.len()),
@@ -444,7 +815,7 @@ fn type_check_expr(
                                 .into_iter()
                                 .skip(idx_dims)
                                 .collect::<Vec<i64>>();
-                            Ok(Type::Tensor {sz: sz.clone(), shape: res_shape})
+                            Ok(Type::Tensor {sz: sz.clone(), shape: res_shape, strides: None})
                         } else {
                             let sh = shape.iter().map(|i| i.to_string()).join(",");
                             py_type_error!(i, "Indexing with {idx_dims} dimensions on tensor of shape [{sh}]")
@@ -454,12 +825,12 @@ fn type_check_expr(
                     }?;
                     match idx.get_type() {
                         Type::Tensor {shape, ..} if shape.len() == 0 => {
-                            let expected_ty = Type::Tensor {sz: ElemSize::I64, shape: vec![]};
+                            let expected_ty = Type::Tensor {sz: ElemSize::I64, shape: vec![], strides: None};
                             Ok((elem_ty, coerce_type(idx, &expected_ty)?))
                         },
                         Type::Tuple {elems} => {
                             let expected_types = elems.iter()
-                                .map(|_| Type::Tensor {sz: ElemSize::I64, shape: vec![]})
+                                .map(|_| Type::Tensor {sz: ElemSize::I64, shape: vec![], strides: None})
                                 .collect::<Vec<Type>>();
                             let expected_ty = Type::Tuple {elems: expected_types};
                             Ok((elem_ty, coerce_type(idx, &expected_ty)?))
@@ -494,10 +865,74 @@ fn type_check_expr(
             let args = type_check_exprs(vars, args)?;
             type_check_builtin(func, args, i)
         },
+        Expr::Slice {lo, hi, step, i, ..} => {
+            let lo = type_check_slice_bound(vars, lo)?;
+            let hi = type_check_slice_bound(vars, hi)?;
+            let step = type_check_slice_bound(vars, step)?;
+            let ty = Type::Tensor {sz: ElemSize::I64, shape: vec![], strides: None};
+            Ok(Expr::Slice {lo, hi, step, ty, i})
+        },
+        e @ Expr::Broadcast {..} => Ok(e),
         e @ Expr::Convert {..} => Ok(e)
     }
 }
 
+/// Compute the result type of subscripting a tensor of the given shape with a positional sequence
+/// of index entries (integers and/or slices). A full integer index removes its axis from both the
+/// shape and the strides, whereas a slice retains the axis with a new extent and multiplies its
+/// stride by the slice step. Axes not named by any entry are retained unchanged.
+fn type_check_tensor_index(
+    sz: &ElemSize,
+    shape: &Vec<i64>,
+    base_strides: &Vec<i64>,
+    entries: &Vec<Expr>,
+    i: &Info
+) -> PyResult<Type> {
+    if entries.len() > shape.len() {
+        let sh = shape.iter().map(|i| i.to_string()).join(",");
+        return py_type_error!(i, "Indexing with {0} dimensions on tensor of shape [{sh}]", entries.len());
+    }
+    let mut res_shape = vec![];
+    let mut res_strides = vec![];
+    for (axis, entry) in entries.iter().enumerate() {
+        match entry {
+            Expr::Slice {lo, hi, step, ..} => {
+                // Distinguish an absent bound (use the default) from a present but non-constant one
+                // (which forces a dynamic result): `None` here means dynamic, `Some(None)` absent,
+                // and `Some(Some(v))` a concrete bound.
+                let resolve = |b: &Option<Box<Expr>>| match b {
+                    None => Some(None),
+                    Some(e) => const_int(e).map(Some)
+                };
+                let (extent, stride) = match (resolve(lo), resolve(hi), resolve(step)) {
+                    (Some(lo), Some(hi), Some(step)) => {
+                        let extent = slice_extent(shape[axis], lo, hi, step);
+                        let stride = match base_strides[axis] {
+                            s if s >= 0 => s * step.unwrap_or(1),
+                            _ => -1
+                        };
+                        (extent, stride)
+                    },
+                    _ => (-1, -1)
+                };
+                res_shape.push(extent);
+                res_strides.push(stride);
+            },
+            // A scalar index consumes the axis; it is dropped from the result.
+            _ => {}
+        }
+    }
+    // Retain any trailing axes not named by an index entry.
+    res_shape.extend_from_slice(&shape[entries.len()..]);
+    res_strides.extend_from_slice(&base_strides[entries.len()..]);
+    let strides = if res_strides == contiguous_strides(&res_shape) {
+        None
+    } else {
+        Some(res_strides)
+    };
+    Ok(Type::Tensor {sz: sz.clone(), shape: res_shape, strides})
+}
+
 fn type_check_exprs(
     vars: &BTreeMap<Name, Type>,
     exprs: Vec<Expr>
@@ -538,7 +973,7 @@ fn type_check_stmt(
             let hi = type_check_expr(&vars, hi)?;
             let hi = ensure_scalar_type(hi, ElemSize::I64)?;
             let mut body_vars = vars.clone();
-            body_vars.insert(var.clone(), Type::Tensor {sz: ElemSize::I64, shape: vec![]});
+            body_vars.insert(var.clone(), Type::Tensor {sz: ElemSize::I64, shape: vec![], strides: None});
             let (_, body) = type_check_stmts(body_vars, body)?;
             Ok((vars, Stmt::For {var, lo, hi, step, body, i}))
         },
@@ -621,7 +1056,7 @@ mod test {
     }
 
     fn scalar_type(sz: ElemSize) -> Type {
-        Type::Tensor {sz, shape: vec![]}
+        Type::Tensor {sz, shape: vec![], strides: None}
     }
 
     fn bool_type() -> Type {
@@ -658,7 +1093,25 @@ mod test {
 
     #[test]
     fn lub_elem_size_int_float() {
-        test_lub_elem_size_fail(&ElemSize::I32, &ElemSize::F32)
+        // A narrow integer promotes to the float, while a 64-bit integer widens the result to
+        // float64 to preserve its precision.
+        test_lub_elem_size_ok(&ElemSize::I32, &ElemSize::F32, ElemSize::F32);
+        test_lub_elem_size_ok(&ElemSize::I64, &ElemSize::F32, ElemSize::F64);
+        test_lub_elem_size_ok(&ElemSize::U8, &ElemSize::F64, ElemSize::F64);
+    }
+
+    #[test]
+    fn slice_extent_negative_step_on_empty_axis_is_empty() {
+        // A zero-length axis has nothing for a reversed slice (e.g. `x[::-1]`) to select; this
+        // must not reach the `(0, n - 1)` clamp in `slice_extent`, which is an invalid range when
+        // `n == 0`.
+        assert_eq!(slice_extent(0, None, None, Some(-1)), 0);
+        assert_eq!(slice_extent(0, Some(0), Some(0), Some(-1)), 0);
+    }
+
+    #[test]
+    fn lub_elem_size_bool_float_fails() {
+        test_lub_elem_size_fail(&ElemSize::Bool, &ElemSize::F32)
     }
 
     fn test_lub_type_ok(lty: Type, rty: Type, expected: Type) {
@@ -691,11 +1144,19 @@ mod test {
 
     #[test]
     fn lub_type_elem_incompatible() {
+        // A boolean and a float remain genuinely incompatible; only int/float mixes now promote.
         let ty1 = scalar_type(ElemSize::F32);
-        let ty2 = scalar_type(ElemSize::I8);
+        let ty2 = scalar_type(ElemSize::Bool);
         test_lub_type_fail(ty1, ty2)
     }
 
+    #[test]
+    fn lub_type_elem_int_float_promotes() {
+        let ty1 = scalar_type(ElemSize::F32);
+        let ty2 = scalar_type(ElemSize::I8);
+        test_lub_type_ok(ty1, ty2, scalar_type(ElemSize::F32))
+    }
+
     #[test]
     fn lub_type_bool_eq() {
         let ty = scalar_type(ElemSize::Bool);
@@ -704,21 +1165,30 @@ mod test {
 
     #[test]
     fn lub_type_tensor_equal_ok() {
-        let ty = Type::Tensor {sz: ElemSize::I32, shape: vec![5]};
+        let ty = Type::Tensor {sz: ElemSize::I32, shape: vec![5], strides: None};
         test_lub_type_ok(ty.clone(), ty.clone(), ty.clone())
     }
 
     #[test]
-    fn lub_type_tensor_compatible_fails() {
-        let ty1 = Type::Tensor {sz: ElemSize::F32, shape: vec![5]};
-        let ty2 = Type::Tensor {sz: ElemSize::F64, shape: vec![5]};
-        test_lub_type_fail(ty1, ty2)
+    fn lub_type_tensor_compatible_elem_sizes() {
+        // Two tensors of the same shape with compatible element sizes now unify to the wider
+        // element size, following broadcasting promotion.
+        let ty1 = Type::Tensor {sz: ElemSize::F32, shape: vec![5], strides: None};
+        let ty2 = Type::Tensor {sz: ElemSize::F64, shape: vec![5], strides: None};
+        test_lub_type_ok(ty1, ty2, Type::Tensor {sz: ElemSize::F64, shape: vec![5], strides: None})
+    }
+
+    #[test]
+    fn lub_type_tensor_broadcast() {
+        let ty1 = Type::Tensor {sz: ElemSize::F32, shape: vec![5, 1], strides: None};
+        let ty2 = Type::Tensor {sz: ElemSize::F32, shape: vec![1, 6], strides: None};
+        test_lub_type_ok(ty1, ty2, Type::Tensor {sz: ElemSize::F32, shape: vec![5, 6], strides: None})
     }
 
     #[test]
-    fn lub_type_tensor_different_shape_fails() {
-        let ty1 = Type::Tensor {sz: ElemSize::F32, shape: vec![5]};
-        let ty2 = Type::Tensor {sz: ElemSize::F32, shape: vec![4]};
+    fn lub_type_tensor_incompatible_shape_fails() {
+        let ty1 = Type::Tensor {sz: ElemSize::F32, shape: vec![5], strides: None};
+        let ty2 = Type::Tensor {sz: ElemSize::F32, shape: vec![4], strides: None};
         test_lub_type_fail(ty1, ty2)
     }
 
@@ -819,6 +1289,58 @@ mod test {
         assert_eq!(res, bool_type());
     }
 
+    #[test]
+    fn type_check_binop_int_float_promotion() {
+        // i32 + f32 promotes to f32, inserting a Convert on the integer operand so both sides share
+        // the final element size.
+        let lhs = Expr::Var {id: var("x"), ty: scalar_type(ElemSize::I32), i: Info::default()};
+        let rhs = Expr::Var {id: var("y"), ty: scalar_type(ElemSize::F32), i: Info::default()};
+        let (lhs, ty, rhs) = type_check_binop(lhs, &BinOp::Add, rhs, &Info::default()).unwrap();
+        assert_eq!(ty, scalar_type(ElemSize::F32));
+        if let Expr::Convert {e, ty} = *lhs {
+            assert_eq!(e.get_type().clone(), scalar_type(ElemSize::I32));
+            assert_eq!(ty, scalar_type(ElemSize::F32));
+        } else {
+            assert!(false);
+        }
+        // The float operand already has the result element size and is left untouched.
+        assert!(matches!(*rhs, Expr::Var {..}));
+    }
+
+    #[test]
+    fn type_check_binop_tensor_broadcast() {
+        // [5,1] + [1,6] broadcasts to [5,6], and each operand is wrapped in a Broadcast node
+        // recording the result shape.
+        let lty = Type::Tensor {sz: ElemSize::F32, shape: vec![5, 1], strides: None};
+        let rty = Type::Tensor {sz: ElemSize::F32, shape: vec![1, 6], strides: None};
+        let lhs = Expr::Var {id: var("a"), ty: lty, i: Info::default()};
+        let rhs = Expr::Var {id: var("b"), ty: rty, i: Info::default()};
+        let (lhs, ty, rhs) = type_check_binop(lhs, &BinOp::Add, rhs, &Info::default()).unwrap();
+        assert_eq!(ty, Type::Tensor {sz: ElemSize::F32, shape: vec![5, 6], strides: None});
+        assert!(matches!(*lhs, Expr::Broadcast {shape, ..} if shape == vec![5, 6]));
+        assert!(matches!(*rhs, Expr::Broadcast {shape, ..} if shape == vec![5, 6]));
+    }
+
+    #[test]
+    fn type_check_binop_tensor_broadcast_prepended_dim() {
+        // [3] * [4,3] broadcasts to [4,3] by left-padding the shorter shape with a leading 1.
+        let lty = Type::Tensor {sz: ElemSize::F32, shape: vec![3], strides: None};
+        let rty = Type::Tensor {sz: ElemSize::F32, shape: vec![4, 3], strides: None};
+        let lhs = Expr::Var {id: var("a"), ty: lty, i: Info::default()};
+        let rhs = Expr::Var {id: var("b"), ty: rty, i: Info::default()};
+        let res = test_tc_binop(lhs, BinOp::Mul, rhs).unwrap();
+        assert_eq!(res, Type::Tensor {sz: ElemSize::F32, shape: vec![4, 3], strides: None});
+    }
+
+    #[test]
+    fn type_check_binop_tensor_broadcast_incompatible_fails() {
+        let lty = Type::Tensor {sz: ElemSize::F32, shape: vec![5], strides: None};
+        let rty = Type::Tensor {sz: ElemSize::F32, shape: vec![4], strides: None};
+        let lhs = Expr::Var {id: var("a"), ty: lty, i: Info::default()};
+        let rhs = Expr::Var {id: var("b"), ty: rty, i: Info::default()};
+        assert!(type_check_binop(lhs, &BinOp::Add, rhs, &Info::default()).is_err());
+    }
+
     fn make_map<'a>(entries: Vec<(&'a str, Type)>) -> BTreeMap<Name, Type> {
         entries.into_iter()
             .map(|(id, ty)| (Name::new(id.to_string()), ty))
@@ -894,7 +1416,7 @@ mod test {
 
     #[test]
     fn type_check_expr_tensor_lookup() {
-        let tensor_ty = Type::Tensor {sz: ElemSize::F32, shape: vec![5]};
+        let tensor_ty = Type::Tensor {sz: ElemSize::F32, shape: vec![5], strides: None};
         let vars = make_map(vec![("x", tensor_ty.clone())]);
         let v = Expr::Subscript {
             target: Box::new(Expr::Var {id: var("x"), ty: Type::Unknown, i: Info::default()}),
@@ -915,7 +1437,7 @@ mod test {
 
     #[test]
     fn type_check_expr_tensor_lookup_with_conversion() {
-        let tensor_ty = Type::Tensor {sz: ElemSize::F32, shape: vec![5]};
+        let tensor_ty = Type::Tensor {sz: ElemSize::F32, shape: vec![5], strides: None};
         let vars = make_map(vec![
             ("x", tensor_ty.clone()),
             ("y", scalar_type(ElemSize::I32))
@@ -946,7 +1468,7 @@ mod test {
 
     #[test]
     fn type_check_expr_tensor_slicing() {
-        let tensor_ty = Type::Tensor {sz: ElemSize::F32, shape: vec![5,6,4]};
+        let tensor_ty = Type::Tensor {sz: ElemSize::F32, shape: vec![5,6,4], strides: None};
         let vars = make_map(vec![
             ("x", tensor_ty.clone()),
         ]);
@@ -967,11 +1489,129 @@ mod test {
         };
         let r = type_check_expr(&vars, v);
         if let Expr::Subscript {target, idx, ty, ..} = r.unwrap() {
-            assert_eq!(ty, Type::Tensor {sz: ElemSize::F32, shape: vec![4]});
+            assert_eq!(ty, Type::Tensor {sz: ElemSize::F32, shape: vec![4], strides: None});
             assert_eq!(target.get_type().clone(), tensor_ty);
             assert_eq!(idx.get_type().clone(), tuple_ty);
         } else {
             assert!(false);
         }
     }
+
+    fn int_lit(v: i64) -> Expr {
+        Expr::Int {v, ty: Type::Unknown, i: Info::default()}
+    }
+
+    #[test]
+    fn type_check_expr_tuple_index() {
+        // (bool, f32)[1] projects out the second element type.
+        let tuple_ty = Type::Tuple {elems: vec![bool_type(), scalar_type(ElemSize::F32)]};
+        let vars = make_map(vec![("x", tuple_ty)]);
+        let r = type_check_expr(&vars, subscript("x", int_lit(1))).unwrap();
+        assert_eq!(r.get_type().clone(), scalar_type(ElemSize::F32));
+    }
+
+    #[test]
+    fn type_check_expr_tuple_index_out_of_range() {
+        let tuple_ty = Type::Tuple {elems: vec![bool_type(), scalar_type(ElemSize::F32)]};
+        let vars = make_map(vec![("x", tuple_ty)]);
+        assert!(type_check_expr(&vars, subscript("x", int_lit(2))).is_err());
+    }
+
+    #[test]
+    fn type_check_expr_tensor_negative_index() {
+        // x[-1] on a [5] tensor normalizes to x[4].
+        let tensor_ty = Type::Tensor {sz: ElemSize::F32, shape: vec![5], strides: None};
+        let vars = make_map(vec![("x", tensor_ty)]);
+        let r = type_check_expr(&vars, subscript("x", int_lit(-1))).unwrap();
+        if let Expr::Subscript {idx, ty, ..} = r {
+            assert_eq!(ty, scalar_type(ElemSize::F32));
+            assert!(matches!(*idx, Expr::Int {v: 4, ..}));
+        } else {
+            assert!(false);
+        }
+    }
+
+    #[test]
+    fn type_check_expr_tensor_index_out_of_bounds() {
+        let tensor_ty = Type::Tensor {sz: ElemSize::F32, shape: vec![5], strides: None};
+        let vars = make_map(vec![("x", tensor_ty)]);
+        assert!(type_check_expr(&vars, subscript("x", int_lit(5))).is_err());
+    }
+
+    fn slice(lo: Option<i64>, hi: Option<i64>, step: Option<i64>) -> Expr {
+        let bound = |o: Option<i64>| o.map(|v| Box::new(int_lit(v)));
+        Expr::Slice {
+            lo: bound(lo), hi: bound(hi), step: bound(step), ty: Type::Unknown, i: Info::default()
+        }
+    }
+
+    fn subscript(target: &str, idx: Expr) -> Expr {
+        Expr::Subscript {
+            target: Box::new(Expr::Var {id: var(target), ty: Type::Unknown, i: Info::default()}),
+            idx: Box::new(idx),
+            ty: Type::Unknown,
+            i: Info::default()
+        }
+    }
+
+    #[test]
+    fn type_check_expr_strided_slice() {
+        // x[1:5:2, 3] on a [10,6] tensor retains the sliced axis with extent 2 and drops the
+        // integer-indexed axis.
+        let tensor_ty = Type::Tensor {sz: ElemSize::F32, shape: vec![10, 6], strides: None};
+        let vars = make_map(vec![("x", tensor_ty)]);
+        let idx = Expr::Tuple {
+            elems: vec![slice(Some(1), Some(5), Some(2)), int_lit(3)],
+            ty: Type::Unknown,
+            i: Info::default()
+        };
+        let r = type_check_expr(&vars, subscript("x", idx)).unwrap();
+        if let Type::Tensor {sz, shape, strides} = r.get_type() {
+            assert_eq!(sz, &ElemSize::F32);
+            assert_eq!(shape, &vec![2]);
+            // Stride of the retained axis is the original row stride (6) times the step (2).
+            assert_eq!(strides, &Some(vec![12]));
+        } else {
+            assert!(false);
+        }
+    }
+
+    #[test]
+    fn type_check_expr_negative_step_slice() {
+        // x[8:0:-2] on a [10] tensor walks downward over indices 8,6,4,2 for an extent of 4.
+        let tensor_ty = Type::Tensor {sz: ElemSize::F32, shape: vec![10], strides: None};
+        let vars = make_map(vec![("x", tensor_ty)]);
+        let r = type_check_expr(&vars, subscript("x", slice(Some(8), Some(0), Some(-2)))).unwrap();
+        if let Type::Tensor {shape, strides, ..} = r.get_type() {
+            assert_eq!(shape, &vec![4]);
+            // A reversed slice is non-contiguous, so explicit strides are retained.
+            assert_eq!(strides, &Some(vec![-2]));
+        } else {
+            assert!(false);
+        }
+    }
+
+    #[test]
+    fn type_check_expr_dynamic_slice_bound() {
+        // A non-constant bound makes the sliced extent dynamic rather than erroring.
+        let tensor_ty = Type::Tensor {sz: ElemSize::F32, shape: vec![10], strides: None};
+        let vars = make_map(vec![
+            ("x", tensor_ty),
+            ("a", scalar_type(ElemSize::I64))
+        ]);
+        let idx = Expr::Slice {
+            lo: Some(Box::new(Expr::Var {id: var("a"), ty: Type::Unknown, i: Info::default()})),
+            hi: Some(Box::new(int_lit(8))),
+            step: None,
+            ty: Type::Unknown,
+            i: Info::default()
+        };
+        let r = type_check_expr(&vars, subscript("x", idx)).unwrap();
+        if let Type::Tensor {sz, shape, ..} = r.get_type() {
+            assert_eq!(sz, &ElemSize::F32);
+            assert_eq!(shape, &vec![-1]);
+        } else {
+            assert!(false);
+        }
+    }
 }